@@ -1,14 +1,38 @@
 //! JSON RPC client for Ethereum nodes (tested against Infura).
 //! ref: https://eth.wiki/json-rpc/API
 
-use anyhow::{Context, Result};
-use clarity::{Address, Uint256};
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
 
-pub use crate::jsonrpc::Url;
-use crate::types::{BlockNumber, CallRequest, TransactionReceipt, H256};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
+pub use crate::jsonrpc::{Auth, ClientError, Url};
+use crate::types::{
+    parse_quantity, AccessList, AccountProof, Address, Block, BlockHeader, BlockId, BlockNumber,
+    Bytes, CallRequest, CallTrace, Erc20Metadata, FeeHistory, Filter, FilterId, Log,
+    ParseQuantityError, SimulationResult, StateOverride, TransactionReceipt, TxParams,
+    TxpoolContent, TxpoolStatus, H256, U256, U64,
+};
+
+use crate::ens;
+use crate::gas::FeeStrategy;
 use crate::jsonrpc as rpc;
+use crate::pending::PendingTransaction;
+use crate::transaction::{contract_address, ChainId, Eip1559Transaction, LegacyTransaction};
+use crate::watch::BlockWatcher;
+use crate::{address_from_secret_key, function_selector, SecretKey};
+
+type Result<T> = std::result::Result<T, ClientError>;
 
+/// A client for the Ethereum JSON-RPC API.
+///
+/// Clones share the underlying connection pool, clone the client rather than
+/// constructing a new one to use it from several threads.
 #[derive(Debug, Clone)]
 pub struct Client {
     inner: rpc::Client,
@@ -21,6 +45,91 @@ impl Client {
         }
     }
 
+    /// Construct a client that sends requests with an existing `agent`, to
+    /// share its connection pool and configuration.
+    pub fn with_agent(base_url: Url, agent: ureq::Agent) -> Self {
+        Client {
+            inner: rpc::Client::with_agent(base_url, agent),
+        }
+    }
+
+    /// Construct a client that connects via the HTTP proxy at `proxy`.
+    pub fn with_proxy(base_url: Url, proxy: Url) -> Result<Self> {
+        Ok(Client {
+            inner: rpc::Client::with_proxy(base_url, proxy)?,
+        })
+    }
+
+    /// Construct a client that uses the proxy set in the `HTTPS_PROXY` or
+    /// `HTTP_PROXY` environment variable, if any.
+    pub fn with_proxy_from_env(base_url: Url) -> Result<Self> {
+        Ok(Client {
+            inner: rpc::Client::with_proxy_from_env(base_url)?,
+        })
+    }
+
+    /// Authenticates every request with `auth`, needed for gated endpoints.
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.inner = self.inner.with_auth(auth);
+        self
+    }
+
+    /// Sends the HTTP header `name: value` with every request, e.g. an API
+    /// key header required by a node provider.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.inner = self.inner.with_header(name, value);
+        self
+    }
+
+    /// Sends every request with JSON-RPC version `version` (default "2.0").
+    pub fn with_jsonrpc_version(mut self, version: &str) -> Self {
+        self.inner = self.inner.with_jsonrpc_version(version);
+        self
+    }
+
+    /// Prepends `prefix` to the method name of every request.
+    pub fn with_method_prefix(mut self, prefix: &str) -> Self {
+        self.inner = self.inner.with_method_prefix(prefix);
+        self
+    }
+
+    /// Records every request and its response to the JSON file at `path`,
+    /// for later use with `replaying`.
+    pub fn recording<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.inner = self.inner.recording(path);
+        self
+    }
+
+    /// Serves responses from a file written by `recording` instead of
+    /// contacting the node, useful for deterministic tests.
+    pub fn replaying<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        self.inner = self.inner.replaying(path)?;
+        Ok(self)
+    }
+
+    /// Calls `logger` with the serialized request and raw response body of
+    /// every RPC call, useful when debugging.
+    pub fn with_logger<F>(mut self, logger: F) -> Self
+    where
+        F: Fn(&str, &str) + Send + Sync + 'static,
+    {
+        self.inner = self.inner.with_logger(logger);
+        self
+    }
+
+    /// Sends a request for any RPC `method`, e.g. a vendor specific one such
+    /// as `erigon_getHeaderByNumber` that has no method of its own here.
+    /// `params` is typically a tuple, array or vector of parameters.
+    pub fn raw_request<P, R>(&self, method: &str, params: P) -> Result<R>
+    where
+        P: Debug + Serialize,
+        R: Debug + DeserializeOwned,
+    {
+        let result = self.inner.send(rpc::Request::v2(method, params))?;
+
+        Ok(result)
+    }
+
     /// Execute RPC method: `web3_clientVersion`. Return version string:
     /// "Geth/v1.10.2-unstable-f304290b-20210323/linux-amd64/go1.13.8"
     pub fn client_version(&self) -> Result<String> {
@@ -35,25 +144,159 @@ impl Client {
     pub fn chain_id(&self) -> Result<u32> {
         let chain_id = self
             .inner
-            .send::<Vec<()>, String>(rpc::Request::v2("net_version", vec![]))
-            .context("failed to fetch net version")?;
+            .send::<Vec<()>, String>(rpc::Request::v2("net_version", vec![]))?;
         let chain_id: u32 = chain_id.parse()?;
 
         Ok(chain_id)
     }
 
+    /// Execute RPC method: `net_peerCount`. Return the number of peers
+    /// currently connected to the node.
+    pub fn peer_count(&self) -> Result<u64> {
+        let count = self
+            .inner
+            .send::<Vec<()>, U64>(rpc::Request::v2("net_peerCount", vec![]))?;
+
+        Ok(count.as_u64())
+    }
+
+    /// Execute RPC method: `net_listening`. Return true if the node is
+    /// listening for network connections.
+    pub fn is_listening(&self) -> Result<bool> {
+        let listening = self
+            .inner
+            .send::<Vec<()>, bool>(rpc::Request::v2("net_listening", vec![]))?;
+
+        Ok(listening)
+    }
+
+    /// Execute RPC method: `eth_mining`. Return true if the node is mining.
+    pub fn is_mining(&self) -> Result<bool> {
+        let mining = self
+            .inner
+            .send::<Vec<()>, bool>(rpc::Request::v2("eth_mining", vec![]))?;
+
+        Ok(mining)
+    }
+
+    /// Execute RPC method: `eth_hashrate`. Return the number of hashes per
+    /// second the node is mining with.
+    pub fn hashrate(&self) -> Result<U256> {
+        let hashrate = self
+            .inner
+            .send::<Vec<()>, U256>(rpc::Request::v2("eth_hashrate", vec![]))?;
+
+        Ok(hashrate)
+    }
+
+    /// Execute RPC method: `eth_sign`. Return the 65 byte signature of
+    /// `data` by `account`, the node hashes `data` as per EIP-191 (see
+    /// `hash_personal_message`). The account must be unlocked on the node.
+    pub fn eth_sign(&self, account: Address, data: Bytes) -> Result<Bytes> {
+        let signature = self.inner.send(rpc::Request::v2("eth_sign", vec![
+            rpc::serialize(account)?,
+            rpc::serialize(data)?,
+        ]))?;
+
+        Ok(signature)
+    }
+
+    /// Execute RPC method: `txpool_status`. Return the number of pending and
+    /// queued transactions (geth only).
+    pub fn txpool_status(&self) -> Result<TxpoolStatus> {
+        let status = self
+            .inner
+            .send::<Vec<()>, TxpoolStatus>(rpc::Request::v2("txpool_status", vec![]))?;
+
+        Ok(status)
+    }
+
+    /// Execute RPC method: `txpool_content`. Return the pending and queued
+    /// transactions (geth only).
+    pub fn txpool_content(&self) -> Result<TxpoolContent> {
+        let content = self
+            .inner
+            .send::<Vec<()>, TxpoolContent>(rpc::Request::v2("txpool_content", vec![]))?;
+
+        Ok(content)
+    }
+
     /// Execute RPC method: `eth_sendRawTransaction`. Return transaction hash.
     pub fn send_raw_transaction(&self, transaction_hex: String) -> Result<H256> {
+        validate_raw_transaction(&transaction_hex)?;
+
         let tx_hash = self
             .inner
             .send(rpc::Request::v2("eth_sendRawTransaction", vec![
                 transaction_hex,
-            ]))
-            .context("failed to send raw transaction")?;
+            ]))?;
 
         Ok(tx_hash)
     }
 
+    /// Like `send_raw_transaction` but takes the signed transaction as bytes.
+    pub fn send_raw_transaction_bytes(&self, raw: &[u8]) -> Result<H256> {
+        self.send_raw_transaction(format!("0x{}", hex::encode(raw)))
+    }
+
+    /// Deploys a contract: sends a contract creation transaction with
+    /// `init_code` (see `transaction::deploy_contract_data`) signed by `sk`,
+    /// using the pending nonce of its account. Return the transaction hash
+    /// and the address the contract will have once mined.
+    pub fn deploy(
+        &self,
+        sk: &SecretKey,
+        chain_id: ChainId,
+        init_code: Vec<u8>,
+        gas_price: U256,
+        gas_limit: U256,
+    ) -> Result<(H256, Address)> {
+        let sender = address_from_secret_key(sk);
+        let nonce = self.get_pending_nonce(sender)?;
+
+        let tx = LegacyTransaction {
+            nonce: nonce.into(),
+            gas_price,
+            gas_limit,
+            to: None,
+            value: U256::zero(),
+            data: init_code.into(),
+        };
+        let hash = self.send_raw_transaction(tx.sign(sk, chain_id).to_hex())?;
+
+        let contract = contract_address(sender, nonce);
+        Ok((hash, contract))
+    }
+
+    /// Like `send_raw_transaction` but returns a handle that can wait for the
+    /// transaction to be mined.
+    pub fn send_raw_transaction_pending(
+        &self,
+        transaction_hex: String,
+    ) -> Result<PendingTransaction<'_>> {
+        let hash = self.send_raw_transaction(transaction_hex)?;
+
+        Ok(PendingTransaction::new(self, hash))
+    }
+
+    /// Polls `eth_getTransactionReceipt` until the transaction is mined or
+    /// `max_wait` has passed, returning `None` on timeout.
+    ///
+    /// The delay between polls starts at `interval` and doubles after each
+    /// poll, up to eight times `interval`. Use `Duration::MAX` to wait
+    /// forever. Short for `PendingTransaction::await_receipt(1)`.
+    pub fn poll_receipt(
+        &self,
+        hash: H256,
+        interval: Duration,
+        max_wait: Duration,
+    ) -> Result<Option<TransactionReceipt>> {
+        PendingTransaction::new(self, hash)
+            .interval(interval)
+            .timeout(max_wait)
+            .await_receipt(1)
+    }
+
     /// Execute RPC method: `eth_getTransactionReceipt`.
     pub fn get_transaction_receipt(
         &self,
@@ -63,60 +306,2226 @@ impl Client {
             .inner
             .send(rpc::Request::v2("eth_getTransactionReceipt", vec![
                 rpc::serialize(transaction_hash)?,
-            ]))
-            .context("failed to get transaction receipt")?;
+            ]))?;
 
         Ok(receipt)
     }
 
+    /// Gets the receipts of `hashes` with a single JSON-RPC batch request.
+    /// Receipts are returned in the same order as `hashes`, `None` for
+    /// transactions the node does not know about or that are still pending.
+    pub fn get_transaction_receipts(
+        &self,
+        hashes: &[H256],
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        if hashes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let requests = hashes
+            .iter()
+            .map(|hash| {
+                Ok(rpc::Request::v2("eth_getTransactionReceipt", vec![
+                    rpc::serialize(hash)?,
+                ]))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.inner.send_batch(requests)?.into_iter().collect()
+    }
+
+    /// Execute RPC method: `debug_traceTransaction` with the `callTracer`.
+    /// Return the tree of calls made by the transaction (geth only).
+    pub fn trace_transaction_calls(&self, transaction_hash: H256) -> Result<CallTrace> {
+        let trace = self
+            .inner
+            .send(rpc::Request::v2("debug_traceTransaction", vec![
+                rpc::serialize(transaction_hash)?,
+                serde_json::json!({ "tracer": "callTracer" }),
+            ]))?;
+
+        Ok(trace)
+    }
+
     /// Execute RPC method: `eth_getTransactionCount`. Return the number of
     /// transactions sent from this address.
-    pub fn get_transaction_count(&self, account: Address, height: BlockNumber) -> Result<u32> {
+    pub fn get_transaction_count(&self, account: Address, height: BlockNumber) -> Result<u64> {
         let count: String = self
             .inner
             .send(rpc::Request::v2("eth_getTransactionCount", vec![
                 rpc::serialize(account)?,
                 rpc::serialize(height)?,
-            ]))
-            .context("failed to get transaction count")?;
+            ]))?;
 
-        let count = u32::from_str_radix(&count[2..], 16)?;
+        let count = u64::try_from(parse_quantity(&count)?)
+            .map_err(|_| ClientError::parse(ParseQuantityError::TooLarge))?;
         Ok(count)
     }
 
-    pub fn get_balance(&self, address: Address, height: BlockNumber) -> Result<Uint256> {
-        let amount: String = self
+    /// Returns the transaction count of `account` as of the latest block.
+    pub fn get_transaction_count_latest(&self, account: Address) -> Result<u64> {
+        self.get_transaction_count(account, BlockNumber::Latest)
+    }
+
+    /// Returns the transaction count of `account` including pending
+    /// transactions, i.e. the nonce to use for the next transaction.
+    pub fn get_pending_nonce(&self, account: Address) -> Result<u64> {
+        self.get_transaction_count(account, BlockNumber::Pending)
+    }
+
+    /// Execute RPC method: `eth_blockNumber`. Return the number of the most
+    /// recent block.
+    pub fn block_number(&self) -> Result<u64> {
+        let number = self
             .inner
-            .send(rpc::Request::v2("eth_getBalance", vec![
-                rpc::serialize(address)?,
-                rpc::serialize(height)?,
-            ]))
-            .context("failed to get balance")?;
-        let amount = Uint256::from_str_radix(&amount, 16)?;
+            .send::<Vec<()>, U64>(rpc::Request::v2("eth_blockNumber", vec![]))?;
+
+        Ok(number.as_u64())
+    }
+
+    /// Execute RPC method: `eth_getBlockByNumber` or `eth_getBlockByHash`
+    /// depending on `id`. Return `None` if the block is not known.
+    ///
+    /// If `full` is true the block contains full transaction objects
+    /// (`Block<Transaction>`), otherwise only transaction hashes
+    /// (`Block<H256>`).
+    pub fn get_block<TX>(&self, id: BlockId, full: bool) -> Result<Option<Block<TX>>>
+    where
+        TX: Debug + DeserializeOwned,
+    {
+        let request = match id {
+            BlockId::Hash(hash) => rpc::Request::v2("eth_getBlockByHash", vec![
+                rpc::serialize(hash)?,
+                rpc::serialize(full)?,
+            ]),
+            BlockId::Number(number) => rpc::Request::v2("eth_getBlockByNumber", vec![
+                rpc::serialize(number)?,
+                rpc::serialize(full)?,
+            ]),
+        };
+        let block = self.inner.send(request)?;
+
+        Ok(block)
+    }
+
+    /// Returns an iterator over the blocks `from..=to`, each block is fetched
+    /// when the iterator reaches it. Block tags are resolved to a block number
+    /// up front.
+    pub fn block_iter<TX>(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        full: bool,
+    ) -> Result<impl Iterator<Item = Result<Block<TX>>> + '_>
+    where
+        TX: Debug + DeserializeOwned,
+    {
+        let from = self.resolve_block_number(from)?;
+        let to = self.resolve_block_number(to)?;
+
+        Ok((from..=to).map(move |number| {
+            self.get_block(BlockNumber::from(number).into(), full)?
+                .ok_or_else(|| block_not_found(number))
+        }))
+    }
+
+    /// Returns the header of the block `number`, the block body is not
+    /// fetched.
+    pub fn get_block_header(&self, number: BlockNumber) -> Result<Option<BlockHeader>> {
+        let header = self
+            .inner
+            .send(rpc::Request::v2("eth_getBlockByNumber", vec![
+                rpc::serialize(number)?,
+                rpc::serialize(false)?,
+            ]))?;
+
+        Ok(header)
+    }
+
+    /// Returns an iterator over the headers of new blocks, polling
+    /// `eth_blockNumber` every `poll_interval`. See `BlockWatcher`.
+    pub fn watch_blocks(&self, poll_interval: Duration) -> BlockWatcher<'_> {
+        BlockWatcher::new(self, poll_interval)
+    }
+
+    /// Execute RPC method: `eth_getLogs`. Return the logs matching `filter`.
+    pub fn get_logs(&self, filter: Filter) -> Result<Vec<Log>> {
+        let logs = self
+            .inner
+            .send(rpc::Request::v2("eth_getLogs", vec![rpc::serialize(
+                filter,
+            )?]))?;
+
+        Ok(logs)
+    }
+
+    /// Like `get_logs` but gives up if the call takes longer than `timeout`,
+    /// e.g. to allow a wide query more time than the client's other calls.
+    /// See `jsonrpc::Client::send_with_timeout`.
+    pub fn get_logs_with_timeout(&self, filter: Filter, timeout: Duration) -> Result<Vec<Log>> {
+        let logs = self.inner.send_with_timeout(
+            rpc::Request::v2("eth_getLogs", vec![rpc::serialize(filter)?]),
+            timeout,
+        )?;
+
+        Ok(logs)
+    }
+
+    /// Like `get_logs` but returns an iterator that reads the logs from the
+    /// response one at a time, for queries matching too many logs to
+    /// comfortably hold in memory.
+    pub fn get_logs_streaming(&self, filter: Filter) -> Result<impl Iterator<Item = Result<Log>>> {
+        let logs = self
+            .inner
+            .send_streaming(rpc::Request::v2("eth_getLogs", vec![rpc::serialize(
+                filter,
+            )?]))?;
+
+        Ok(logs)
+    }
+
+    /// Execute RPC method: `eth_newFilter`. Return the id of the installed
+    /// filter, poll it with `get_filter_changes`.
+    pub fn new_filter(&self, filter: Filter) -> Result<FilterId> {
+        let id = self
+            .inner
+            .send(rpc::Request::v2("eth_newFilter", vec![rpc::serialize(
+                filter,
+            )?]))?;
+
+        Ok(id)
+    }
+
+    /// Execute RPC method: `eth_getFilterChanges`. Return the logs matching
+    /// the filter `id` since it was installed or last polled.
+    pub fn get_filter_changes(&self, id: &FilterId) -> Result<Vec<Log>> {
+        let logs = self
+            .inner
+            .send(rpc::Request::v2("eth_getFilterChanges", vec![
+                rpc::serialize(id)?,
+            ]))?;
+
+        Ok(logs)
+    }
+
+    /// Execute RPC method: `eth_uninstallFilter`. Return whether the filter
+    /// `id` was uninstalled.
+    pub fn uninstall_filter(&self, id: FilterId) -> Result<bool> {
+        let uninstalled = self
+            .inner
+            .send(rpc::Request::v2("eth_uninstallFilter", vec![
+                rpc::serialize(id)?,
+            ]))?;
+
+        Ok(uninstalled)
+    }
+
+    /// Gets the logs matching `filter`, querying at most `chunk_size` blocks
+    /// at a time to stay within provider limits. Logs are returned in block
+    /// order.
+    ///
+    /// Block tags (e.g. `Latest`) in the range are resolved to a block
+    /// number first, filters by block hash are sent as a single query.
+    pub fn get_logs_chunked(&self, filter: Filter, chunk_size: u64) -> Result<Vec<Log>> {
+        self.get_logs_in_chunks(filter, chunk_size, false)
+    }
+
+    /// Like `get_logs_chunked` but for providers that limit the number of
+    /// results rather than the block range: when a query fails because it
+    /// matches too many logs the range is split in half and each half retried,
+    /// down to single block queries if needed.
+    pub fn get_logs_chunked_bisecting(&self, filter: Filter, chunk_size: u64) -> Result<Vec<Log>> {
+        self.get_logs_in_chunks(filter, chunk_size, true)
+    }
+
+    fn get_logs_in_chunks(
+        &self,
+        filter: Filter,
+        chunk_size: u64,
+        bisect: bool,
+    ) -> Result<Vec<Log>> {
+        if filter.block_hash().is_some() {
+            return self.get_logs(filter);
+        }
+
+        let from = self.resolve_block_number(filter.from_block().unwrap_or(BlockNumber::Latest))?;
+        let to = self.resolve_block_number(filter.to_block().unwrap_or(BlockNumber::Latest))?;
+        let chunk_size = chunk_size.max(1);
+
+        let mut logs = Vec::new();
+        let mut start = from;
+        while start <= to {
+            let end = to.min(start.saturating_add(chunk_size - 1));
+            if bisect {
+                logs.extend(self.get_logs_bisecting(&filter, start, end)?);
+            } else {
+                logs.extend(self.get_logs(filter.with_block_range(start, end))?);
+            }
+
+            match end.checked_add(1) {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+
+        Ok(logs)
+    }
+
+    /// Gets the logs for blocks `start..=end`, splitting the range in half on
+    /// result limit errors.
+    fn get_logs_bisecting(&self, filter: &Filter, start: u64, end: u64) -> Result<Vec<Log>> {
+        match self.get_logs(filter.with_block_range(start, end)) {
+            Err(ClientError::JsonRpc(e)) if e.is_result_limit() && start < end => {
+                let mid = start + (end - start) / 2;
+                log::debug!(
+                    "eth_getLogs for blocks {}-{} hit a result limit, splitting at {}",
+                    start,
+                    end,
+                    mid
+                );
+
+                let mut logs = self.get_logs_bisecting(filter, start, mid)?;
+                logs.extend(self.get_logs_bisecting(filter, mid + 1, end)?);
+                Ok(logs)
+            }
+            result => result,
+        }
+    }
+
+    /// Returns the number of the block `height` refers to.
+    fn resolve_block_number(&self, height: BlockNumber) -> Result<u64> {
+        match height {
+            BlockNumber::Number(number) => Ok(number.as_u64()),
+            BlockNumber::Earliest => Ok(0),
+            BlockNumber::Latest | BlockNumber::Pending => self.block_number(),
+            BlockNumber::Safe | BlockNumber::Finalized => {
+                let block: Option<Block<H256>> = self.get_block(height.into(), false)?;
+                block
+                    .and_then(|block| block.number)
+                    .map(|n| n.as_u64())
+                    .ok_or_else(|| block_not_found(height))
+            }
+        }
+    }
+
+    /// Execute RPC method: `eth_getBlockTransactionCountByNumber`. Return the
+    /// number of transactions in the block.
+    pub fn get_block_transaction_count(&self, height: BlockNumber) -> Result<u64> {
+        let count: U64 = self.inner.send(rpc::Request::v2(
+            "eth_getBlockTransactionCountByNumber",
+            vec![rpc::serialize(height)?],
+        ))?;
+
+        Ok(count.as_u64())
+    }
+
+    /// Execute RPC method: `eth_getBlockReceipts`. Return the receipts of
+    /// all transactions in the block, in block order, or `None` if the node
+    /// does not know the block.
+    pub fn get_block_receipts(
+        &self,
+        block: BlockNumber,
+    ) -> Result<Option<Vec<TransactionReceipt>>> {
+        let receipts = self
+            .inner
+            .send(rpc::Request::v2("eth_getBlockReceipts", vec![
+                rpc::serialize(block)?,
+            ]))?;
+
+        Ok(receipts)
+    }
+
+    pub fn get_balance(&self, address: Address, height: BlockNumber) -> Result<U256> {
+        let amount: String = self.inner.send(rpc::Request::v2("eth_getBalance", vec![
+            rpc::serialize(address)?,
+            rpc::serialize(height)?,
+        ]))?;
+        let amount = parse_quantity(&amount)?;
 
         Ok(amount)
     }
 
-    pub fn gas_price(&self) -> Result<Uint256> {
+    /// Execute RPC method: `eth_getProof`. Return the account proof for
+    /// `address` and storage proofs for each of `storage_keys`.
+    pub fn get_proof(
+        &self,
+        address: Address,
+        storage_keys: &[H256],
+        height: BlockNumber,
+    ) -> Result<AccountProof> {
+        let proof = self.inner.send(rpc::Request::v2("eth_getProof", vec![
+            rpc::serialize(address)?,
+            rpc::serialize(storage_keys)?,
+            rpc::serialize(height)?,
+        ]))?;
+
+        Ok(proof)
+    }
+
+    /// Execute RPC method: `eth_getStorageAt`. Return the word stored at
+    /// `slot` in the storage of `address`.
+    pub fn get_storage_at(
+        &self,
+        address: Address,
+        slot: H256,
+        height: BlockNumber,
+    ) -> Result<H256> {
+        let word = self.inner.send(rpc::Request::v2("eth_getStorageAt", vec![
+            rpc::serialize(address)?,
+            rpc::serialize(slot)?,
+            rpc::serialize(height)?,
+        ]))?;
+
+        Ok(word)
+    }
+
+    /// Returns the implementation behind the EIP-1967 proxy `proxy`, or
+    /// `None` if its implementation slot is empty.
+    ///
+    /// ref: https://eips.ethereum.org/EIPS/eip-1967
+    pub fn get_proxy_implementation(
+        &self,
+        proxy: Address,
+        height: BlockNumber,
+    ) -> Result<Option<Address>> {
+        let slot = EIP1967_IMPLEMENTATION_SLOT
+            .parse()
+            .expect("slot is valid hex");
+        let word = self.get_storage_at(proxy, slot, height)?;
+        if word.is_zero() {
+            return Ok(None);
+        }
+        Ok(Some(Address::from(word)))
+    }
+
+    /// Returns the balance of `account` as of the latest block.
+    pub fn get_balance_latest(&self, account: Address) -> Result<U256> {
+        self.get_balance(account, BlockNumber::Latest)
+    }
+
+    /// Returns the balance of `account` including pending transactions.
+    pub fn get_pending_balance(&self, account: Address) -> Result<U256> {
+        self.get_balance(account, BlockNumber::Pending)
+    }
+
+    /// Gets the balance of each of `addresses`, issuing at most `concurrency`
+    /// requests at a time. Balances are returned in the same order as
+    /// `addresses`.
+    pub fn get_balances(
+        &self,
+        addresses: &[Address],
+        height: BlockNumber,
+        concurrency: usize,
+    ) -> Result<Vec<U256>> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let chunk_size = addresses.len().div_ceil(concurrency.max(1));
+
+        thread::scope(|s| {
+            let workers = addresses
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|address| self.get_balance(*address, height))
+                            .collect::<Result<Vec<_>>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut balances = Vec::with_capacity(addresses.len());
+            for worker in workers {
+                balances.extend(worker.join().expect("balance worker panicked")?);
+            }
+            Ok(balances)
+        })
+    }
+
+    pub fn gas_price(&self) -> Result<U256> {
         let amount = self
             .inner
-            .send::<Vec<()>, String>(rpc::Request::v2("eth_gasPrice", vec![]))
-            .context("failed to get gas price")?;
-        let amount = Uint256::from_str_radix(&amount[2..], 16)?;
+            .send::<Vec<()>, String>(rpc::Request::v2("eth_gasPrice", vec![]))?;
+        let amount = parse_quantity(&amount)?;
 
         Ok(amount)
     }
 
-    pub fn gas_limit(&self, request: CallRequest, height: BlockNumber) -> Result<Uint256> {
-        let gas_limit: String = self
+    /// Returns the current gas price, or a `GasTooHigh` error if it is above
+    /// `max`.
+    pub fn gas_price_capped(&self, max: U256) -> Result<U256> {
+        let price = self.gas_price()?;
+        if price > max {
+            return Err(ClientError::GasTooHigh { price, max });
+        }
+
+        Ok(price)
+    }
+
+    /// Execute RPC method: `eth_feeHistory`. Return the base fees of the
+    /// `block_count` blocks up to `newest` and the priority fees paid at each
+    /// of the `percentiles`.
+    pub fn fee_history(
+        &self,
+        block_count: u64,
+        newest: BlockNumber,
+        percentiles: &[f64],
+    ) -> Result<FeeHistory> {
+        let history = self.inner.send(rpc::Request::v2("eth_feeHistory", vec![
+            rpc::serialize(U64::from(block_count))?,
+            rpc::serialize(newest)?,
+            rpc::serialize(percentiles)?,
+        ]))?;
+
+        Ok(history)
+    }
+
+    /// Execute RPC method: `eth_call`. Return the data returned by the call.
+    ///
+    /// `state_overrides` temporarily replaces account state (balance, code,
+    /// storage, ...) for this call only.
+    pub fn call(
+        &self,
+        request: CallRequest,
+        height: BlockNumber,
+        state_overrides: Option<StateOverride>,
+    ) -> Result<Bytes> {
+        let params = call_params(request, height, state_overrides)?;
+        let data = self.inner.send(rpc::Request::v2("eth_call", params))?;
+
+        Ok(data)
+    }
+
+    /// Simulates sending `request` at block `height` without broadcasting
+    /// anything: `eth_call` checks the transaction does not revert and
+    /// `eth_estimateGas` gives its gas cost. Both see `state_overrides`, if
+    /// any.
+    ///
+    /// A revert is returned as `ClientError::JsonRpc`, use
+    /// `JsonRpcError::revert_reason` to get the decoded reason.
+    pub fn simulate(
+        &self,
+        request: CallRequest,
+        height: BlockNumber,
+        state_overrides: Option<StateOverride>,
+    ) -> Result<SimulationResult> {
+        let output = self.call(request.clone(), height, state_overrides.clone())?;
+
+        let params = call_params(request, height, state_overrides)?;
+        let gas: String = self
             .inner
-            .send(rpc::Request::v2("eth_estimateGas", vec![
+            .send(rpc::Request::v2("eth_estimateGas", params))?;
+        let gas = u64::try_from(parse_quantity(&gas)?)
+            .map_err(|_| ClientError::parse(ParseQuantityError::TooLarge))?;
+
+        Ok(SimulationResult { output, gas })
+    }
+
+    /// Execute RPC method: `eth_createAccessList`. Return the access list the
+    /// call would use and the gas it would consume with that list applied.
+    pub fn create_access_list(
+        &self,
+        request: CallRequest,
+        height: BlockNumber,
+    ) -> Result<(AccessList, u64)> {
+        #[derive(Debug, Deserialize)]
+        struct AccessListResult {
+            #[serde(rename = "accessList")]
+            access_list: AccessList,
+            #[serde(rename = "gasUsed")]
+            gas_used: U64,
+        }
+
+        let result: AccessListResult =
+            self.inner
+                .send(rpc::Request::v2("eth_createAccessList", vec![
+                    rpc::serialize(request)?,
+                    rpc::serialize(height)?,
+                ]))?;
+
+        Ok((result.access_list, result.gas_used.as_u64()))
+    }
+
+    /// Reads the `name()`, `symbol()` and `decimals()` of an ERC-20 token
+    /// contract.
+    pub fn erc20_metadata(&self, token_contract: Address) -> Result<Erc20Metadata> {
+        let to = token_contract;
+        let call = |signature: &str| {
+            let selector = function_selector(signature);
+            let request = CallRequest {
+                to: Some(to),
+                data: Some(selector.to_vec().into()),
+                ..Default::default()
+            };
+            self.call(request, BlockNumber::Latest, None)
+        };
+
+        let name = decode_string(&call("name()")?)?;
+        let symbol = decode_string(&call("symbol()")?)?;
+        let decimals = decode_u8(&call("decimals()")?)?;
+
+        Ok(Erc20Metadata {
+            name,
+            symbol,
+            decimals,
+        })
+    }
+
+    /// Reads the `totalSupply()` of an ERC-20 token contract, in the token's
+    /// base unit.
+    pub fn erc20_total_supply(&self, token_contract: Address) -> Result<U256> {
+        let request = CallRequest {
+            to: Some(token_contract),
+            data: Some(function_selector("totalSupply()").to_vec().into()),
+            ..Default::default()
+        };
+
+        decode_u256(&self.call(request, BlockNumber::Latest, None)?)
+    }
+
+    /// Reads the `allowance(owner, spender)` of an ERC-20 token contract, the
+    /// amount `spender` may transfer on behalf of `owner` in the token's base
+    /// unit.
+    pub fn erc20_allowance(
+        &self,
+        token_contract: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<U256> {
+        let mut data = function_selector("allowance(address,address)").to_vec();
+        data.extend(ethabi::encode(&[
+            ethabi::Token::Address(owner),
+            ethabi::Token::Address(spender),
+        ]));
+        let request = CallRequest {
+            to: Some(token_contract),
+            data: Some(data.into()),
+            ..Default::default()
+        };
+
+        decode_u256(&self.call(request, BlockNumber::Latest, None)?)
+    }
+
+    /// Resolves the ENS name `name`, e.g. `"vitalik.eth"`, to an address.
+    /// Return `None` if the name is not registered. See `ens::resolve`.
+    pub fn resolve_ens(&self, name: &str) -> Result<Option<Address>> {
+        ens::resolve(self, name)
+    }
+
+    /// Looks up the primary ENS name of `address`, verified by resolving it
+    /// back to `address`. Return `None` if there is no such name. See
+    /// `ens::lookup`.
+    pub fn lookup_ens(&self, address: Address) -> Result<Option<String>> {
+        ens::lookup(self, &address)
+    }
+
+    /// Executes `calls` in a single `eth_call` via the Multicall3 contract
+    /// deployed at `MULTICALL3_ADDRESS`. Return the data returned by each
+    /// call, in order.
+    ///
+    /// If any call reverts the whole batch fails.
+    pub fn multicall(
+        &self,
+        calls: Vec<(Address, Bytes)>,
+        height: BlockNumber,
+    ) -> Result<Vec<Bytes>> {
+        let multicall = MULTICALL3_ADDRESS.parse().expect("valid address");
+        self.multicall_at(multicall, calls, height)
+    }
+
+    /// Like `multicall` but for chains where Multicall3 is deployed at a
+    /// different address.
+    pub fn multicall_at(
+        &self,
+        multicall: Address,
+        calls: Vec<(Address, Bytes)>,
+        height: BlockNumber,
+    ) -> Result<Vec<Bytes>> {
+        let calls = calls
+            .into_iter()
+            .map(|(target, data)| {
+                ethabi::Token::Tuple(vec![
+                    ethabi::Token::Address(target),
+                    ethabi::Token::Bool(false), // allowFailure
+                    ethabi::Token::Bytes(data.0),
+                ])
+            })
+            .collect();
+
+        let mut data = function_selector(AGGREGATE3_SIGNATURE).to_vec();
+        data.extend(ethabi::encode(&[ethabi::Token::Array(calls)]));
+
+        let request = CallRequest {
+            to: Some(multicall),
+            data: Some(data.into()),
+            ..Default::default()
+        };
+        let output = self.call(request, height, None)?;
+
+        decode_aggregate3(&output)
+    }
+
+    /// Fetches the pending nonce of `request.from`, the gas price and a gas
+    /// estimate for `request` in a single JSON-RPC batch request.
+    pub fn prepare_transaction(&self, request: CallRequest) -> Result<TxParams> {
+        let from = request.from.ok_or_else(|| {
+            ClientError::InvalidRequest("cannot prepare a transaction without `from`".to_owned())
+        })?;
+
+        let results = self.inner.send_batch::<_, String>(vec![
+            rpc::Request::v2("eth_getTransactionCount", vec![
+                rpc::serialize(from)?,
+                rpc::serialize(BlockNumber::Pending)?,
+            ]),
+            rpc::Request::v2("eth_gasPrice", vec![]),
+            rpc::Request::v2("eth_estimateGas", vec![
                 rpc::serialize(request)?,
-                rpc::serialize(height)?,
-            ]))
-            .context("failed to get gas price")?;
-        let gas_limit = Uint256::from_str_radix(&gas_limit[2..], 16)?;
+                rpc::serialize(BlockNumber::Pending)?,
+            ]),
+        ])?;
+        let mut quantities = results
+            .into_iter()
+            .map(|result| Ok(parse_quantity(&result?)?))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter();
+        let mut next = || quantities.next().expect("one result per request");
+
+        let nonce = next();
+        let nonce =
+            u64::try_from(nonce).map_err(|_| ClientError::parse(ParseQuantityError::TooLarge))?;
+
+        Ok(TxParams {
+            nonce,
+            gas_price: next(),
+            gas_limit: next(),
+        })
+    }
+
+    /// Builds an unsigned EIP-1559 transaction for `request` on `chain_id`,
+    /// with the pending nonce of `request.from`, a gas estimate and the fees
+    /// chosen by `fees`.
+    pub fn prepare_eip1559_transaction(
+        &self,
+        request: CallRequest,
+        chain_id: ChainId,
+        fees: &dyn FeeStrategy,
+    ) -> Result<Eip1559Transaction> {
+        let params = self.prepare_transaction(request.clone())?;
+        let fees = fees.fees(self)?;
+
+        Ok(Eip1559Transaction {
+            chain_id,
+            nonce: params.nonce.into(),
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            gas_limit: params.gas_limit,
+            to: request.to,
+            value: request.value.unwrap_or_default(),
+            data: request.data.unwrap_or_default(),
+            access_list: AccessList::default(),
+        })
+    }
+
+    pub fn gas_limit(&self, request: CallRequest, height: BlockNumber) -> Result<U256> {
+        let gas_limit: String = self.inner.send(rpc::Request::v2("eth_estimateGas", vec![
+            rpc::serialize(request)?,
+            rpc::serialize(height)?,
+        ]))?;
+        let gas_limit = parse_quantity(&gas_limit)?;
 
         Ok(gas_limit)
     }
 }
+
+/// Checks `raw` is `0x` prefixed hex of whole bytes, nodes give confusing
+/// errors otherwise.
+fn validate_raw_transaction(raw: &str) -> Result<()> {
+    let hex = raw.strip_prefix("0x").ok_or_else(|| {
+        ClientError::InvalidRequest("raw transaction must be 0x prefixed hex".to_owned())
+    })?;
+    if hex.is_empty() {
+        return Err(ClientError::InvalidRequest(
+            "raw transaction is empty".to_owned(),
+        ));
+    }
+    if hex.len() % 2 != 0 {
+        return Err(ClientError::InvalidRequest(format!(
+            "raw transaction has an odd number of hex digits: {}",
+            hex.len()
+        )));
+    }
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ClientError::InvalidRequest(
+            "raw transaction is not valid hex".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Address of the Multicall3 contract, the same on most chains.
+///
+/// ref: https://github.com/mds1/multicall
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Storage slot holding the implementation address of an EIP-1967 proxy,
+/// `keccak256("eip1967.proxy.implementation") - 1`.
+const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+const AGGREGATE3_SIGNATURE: &str = "aggregate3((address,bool,bytes)[])";
+
+/// Decodes the `(bool success, bytes returnData)[]` returned by `aggregate3`.
+fn decode_aggregate3(data: &Bytes) -> Result<Vec<Bytes>> {
+    let result_type = ethabi::ParamType::Array(Box::new(ethabi::ParamType::Tuple(vec![
+        ethabi::ParamType::Bool,
+        ethabi::ParamType::Bytes,
+    ])));
+    let tokens = ethabi::decode(&[result_type], &data.0).map_err(ClientError::parse)?;
+
+    let results = match tokens.into_iter().next() {
+        Some(ethabi::Token::Array(results)) => results,
+        _ => return Err(ClientError::parse(ethabi::Error::InvalidData)),
+    };
+    results
+        .into_iter()
+        .map(|result| match result {
+            ethabi::Token::Tuple(fields) => match fields.as_slice() {
+                [_, ethabi::Token::Bytes(data)] => Ok(Bytes(data.clone())),
+                _ => Err(ClientError::parse(ethabi::Error::InvalidData)),
+            },
+            _ => Err(ClientError::parse(ethabi::Error::InvalidData)),
+        })
+        .collect()
+}
+
+/// Returns the parameters of an `eth_call` like request, the state
+/// overrides are omitted if unset since not all nodes accept them.
+fn call_params(
+    request: CallRequest,
+    height: BlockNumber,
+    state_overrides: Option<StateOverride>,
+) -> Result<Vec<serde_json::Value>> {
+    let mut params = vec![rpc::serialize(request)?, rpc::serialize(height)?];
+    if let Some(state_overrides) = state_overrides {
+        params.push(rpc::serialize(state_overrides)?);
+    }
+
+    Ok(params)
+}
+
+/// Decodes an ABI encoded `string`. Some older tokens (e.g. MKR) return a
+/// `bytes32` instead, in which case the bytes up to the first zero are used.
+fn decode_string(data: &Bytes) -> Result<String> {
+    if let Ok(tokens) = ethabi::decode(&[ethabi::ParamType::String], &data.0) {
+        if let Some(ethabi::Token::String(s)) = tokens.into_iter().next() {
+            return Ok(s);
+        }
+    }
+
+    if data.0.len() == 32 {
+        let len = data.0.iter().position(|b| *b == 0).unwrap_or(32);
+        if let Ok(s) = std::str::from_utf8(&data.0[..len]) {
+            return Ok(s.to_owned());
+        }
+    }
+
+    Err(ClientError::parse(ethabi::Error::InvalidData))
+}
+
+/// Decodes an ABI encoded `uint8`.
+fn decode_u8(data: &Bytes) -> Result<u8> {
+    let tokens =
+        ethabi::decode(&[ethabi::ParamType::Uint(8)], &data.0).map_err(ClientError::parse)?;
+    match tokens.into_iter().next() {
+        Some(ethabi::Token::Uint(n)) if n.bits() <= 8 => {
+            Ok(u8::try_from(n.low_u32()).expect("at most 8 bits"))
+        }
+        _ => Err(ClientError::parse(ethabi::Error::InvalidData)),
+    }
+}
+
+/// Decodes an ABI encoded `uint256`.
+fn decode_u256(data: &Bytes) -> Result<U256> {
+    let tokens =
+        ethabi::decode(&[ethabi::ParamType::Uint(256)], &data.0).map_err(ClientError::parse)?;
+    match tokens.into_iter().next() {
+        Some(ethabi::Token::Uint(n)) => Ok(n),
+        _ => Err(ClientError::parse(ethabi::Error::InvalidData)),
+    }
+}
+
+fn block_not_found<D: std::fmt::Display>(block: D) -> ClientError {
+    ClientError::parse(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("node returned no {} block", block),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::gas::{FixedFee, GasSuggestion};
+    use crate::nonce::NonceManager;
+    use crate::test_util::{self, MockServer};
+    use crate::types::{AccountOverride, FilterBuilder, RecoverSenderError, Transaction, H160};
+    use crate::{address_from_public_key, PublicKey};
+
+    #[test]
+    fn json_rpc_error_maps_to_json_rpc_variant() {
+        let server = MockServer::start(|_| test_util::error_response(3, "execution reverted"));
+        let client = Client::new(server.url());
+
+        match client.client_version() {
+            Err(ClientError::JsonRpc(e)) => {
+                assert_eq!(e.code, 3);
+                assert_eq!(e.message, "execution reverted");
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn malformed_response_maps_to_decode_variant() {
+        let server = MockServer::start(|_| "not json".to_owned());
+        let client = Client::new(server.url());
+
+        let res = client.client_version();
+        assert!(matches!(res, Err(ClientError::Decode(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn unparsable_result_maps_to_parse_variant() {
+        let server = MockServer::with_result(json!("not a number"));
+        let client = Client::new(server.url());
+
+        let res = client.chain_id();
+        assert!(matches!(res, Err(ClientError::Parse(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn unreachable_node_maps_to_transport_variant() {
+        // Nothing listens on the discard port.
+        let client = Client::new(Url::parse("http://127.0.0.1:9/").unwrap());
+
+        let res = client.client_version();
+        assert!(matches!(res, Err(ClientError::Transport(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn get_balance_at_finalized_block() {
+        let server = MockServer::with_result(json!("0xde0b6b3a7640000"));
+        let client = Client::new(server.url());
+
+        let balance = client
+            .get_balance(Address::default(), BlockNumber::Finalized)
+            .unwrap();
+        assert_eq!(balance, U256::exp10(18));
+
+        let request = &server.requests()[0];
+        assert_eq!(request.json()["params"][1], "finalized");
+    }
+
+    #[test]
+    fn get_block_transaction_count_of_genesis_block() {
+        let server = MockServer::with_result(json!("0x0"));
+        let client = Client::new(server.url());
+
+        let count = client
+            .get_block_transaction_count(BlockNumber::Earliest)
+            .unwrap();
+        assert_eq!(count, 0);
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.json()["method"],
+            "eth_getBlockTransactionCountByNumber"
+        );
+        assert_eq!(request.json()["params"][0], "earliest");
+    }
+
+    #[test]
+    fn can_get_peer_count() {
+        let server = MockServer::with_result(json!("0x19"));
+        let client = Client::new(server.url());
+
+        assert_eq!(client.peer_count().unwrap(), 25);
+        assert_eq!(server.requests()[0].json()["method"], "net_peerCount");
+    }
+
+    fn block_json(transactions: serde_json::Value) -> serde_json::Value {
+        json!({
+            "number": "0x1b4",
+            "hash": "0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331",
+            "parentHash": "0x9646252be9520f6e71339a8df9c55e4d7619deeb018d2a3f2d21fc165dde5eb5",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "miner": "0x0000000000000000000000000000000000000000",
+            "stateRoot": "0xd5855eb08b3387c0af375e9cdb6acfc05eb8f519e419b874b6ff2ffda7ed1dff",
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "gasUsed": "0x0",
+            "gasLimit": "0x9f759",
+            "extraData": "0x",
+            "timestamp": "0x54e34e8e",
+            "difficulty": "0x27f07",
+            "uncles": [],
+            "transactions": transactions,
+        })
+    }
+
+    #[test]
+    fn get_block_by_number() {
+        let tx_hash = "0x422fb0d5953c0c48cbb42fb58e1c30f5e150441c68374d70ca7d4f191fd56f26";
+        let server = MockServer::with_result(block_json(json!([tx_hash])));
+        let client = Client::new(server.url());
+
+        let block: Block<H256> = client
+            .get_block(BlockNumber::from(436).into(), false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.transactions, vec![tx_hash.parse().unwrap()]);
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["method"], "eth_getBlockByNumber");
+        assert_eq!(request["params"], json!(["0x1b4", false]));
+    }
+
+    #[test]
+    fn get_block_by_hash() {
+        let hash: H256 = "0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331"
+            .parse()
+            .unwrap();
+        let server = MockServer::with_result(block_json(json!([])));
+        let client = Client::new(server.url());
+
+        let block: Block<Transaction> = client.get_block(hash.into(), true).unwrap().unwrap();
+        assert_eq!(block.hash, Some(hash));
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["method"], "eth_getBlockByHash");
+        assert_eq!(request["params"], json!([hash, true]));
+    }
+
+    #[test]
+    fn get_unknown_block_returns_none() {
+        let server = MockServer::with_result(json!(null));
+        let client = Client::new(server.url());
+
+        let block: Option<Block<H256>> = client.get_block(H256::zero().into(), false).unwrap();
+        assert!(block.is_none());
+    }
+
+    /// Answers `eth_call`s like a token contract for USDC would.
+    /// About 25 billion USDC, in its 6 decimal base unit.
+    const USDC_TOTAL_SUPPLY: u64 = 25_112_338_464_092_187;
+
+    fn usdc(request: &test_util::RecordedRequest) -> String {
+        let data = request.json()["params"][0]["data"]
+            .as_str()
+            .unwrap()
+            .to_owned();
+        let selector = hex::decode(&data[2..]).unwrap();
+
+        let token = if selector == function_selector("name()") {
+            ethabi::Token::String("USD Coin".to_owned())
+        } else if selector == function_selector("symbol()") {
+            ethabi::Token::String("USDC".to_owned())
+        } else if selector == function_selector("decimals()") {
+            ethabi::Token::Uint(6.into())
+        } else if selector == function_selector("totalSupply()") {
+            ethabi::Token::Uint(USDC_TOTAL_SUPPLY.into())
+        } else {
+            return test_util::error_response(3, "execution reverted");
+        };
+        let result = format!("0x{}", hex::encode(ethabi::encode(&[token])));
+
+        test_util::response(json!(result))
+    }
+
+    #[test]
+    fn implementation_slot_is_eip1967() {
+        let mut slot = U256::from_big_endian(&crate::keccak256(b"eip1967.proxy.implementation"));
+        slot -= U256::one();
+        let want: H256 = EIP1967_IMPLEMENTATION_SLOT.parse().unwrap();
+
+        assert_eq!(
+            <H256 as crate::types::BigEndianHash>::from_uint(&slot),
+            want
+        );
+    }
+
+    #[test]
+    fn can_read_proxy_implementation() {
+        // Storage of an EIP-1967 proxy, and of a contract that is not one.
+        let server = MockServer::start(|request| {
+            let request = request.json();
+            let address = request["params"][0].as_str().unwrap().to_lowercase();
+            let word = match address.as_str() {
+                "0x4dbd4fc535ac27206064b68ffcf827b0a60bab3f" => {
+                    "0x0000000000000000000000004e6d8fb7c9e6b5a1f0f9e4d5c1f79f4e2d7a8b31"
+                }
+                _ => "0x0000000000000000000000000000000000000000000000000000000000000000",
+            };
+            test_util::response(json!(word))
+        });
+        let client = Client::new(server.url());
+
+        let proxy = Address::from_str("0x4Dbd4fc535Ac27206064B68FfCf827b0A60BAB3f").unwrap();
+        let implementation = client
+            .get_proxy_implementation(proxy, BlockNumber::Latest)
+            .unwrap();
+        assert_eq!(
+            implementation,
+            Some(Address::from_str("0x4e6d8fb7c9e6b5a1f0f9e4d5c1f79f4e2d7a8b31").unwrap())
+        );
+
+        let request = &server.requests()[0].json();
+        assert_eq!(request["method"], "eth_getStorageAt");
+        assert_eq!(request["params"][1], EIP1967_IMPLEMENTATION_SLOT);
+        assert_eq!(request["params"][2], "latest");
+
+        let not_proxy = Address::from_str("0x1111111111111111111111111111111111111111").unwrap();
+        assert_eq!(
+            client
+                .get_proxy_implementation(not_proxy, BlockNumber::Latest)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn can_read_erc20_metadata() {
+        let server = MockServer::start(usdc);
+        let client = Client::new(server.url());
+        let token = Address::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+
+        let metadata = client.erc20_metadata(token).unwrap();
+        assert_eq!(metadata, Erc20Metadata {
+            name: "USD Coin".to_owned(),
+            symbol: "USDC".to_owned(),
+            decimals: 6,
+        });
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(
+            requests[0].json()["params"][0]["to"],
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+    }
+
+    #[test]
+    fn can_read_erc20_total_supply() {
+        let server = MockServer::start(usdc);
+        let client = Client::new(server.url());
+        let token = Address::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+
+        let supply = client.erc20_total_supply(token).unwrap();
+        assert!(!supply.is_zero());
+        assert_eq!(supply, U256::from(USDC_TOTAL_SUPPLY));
+
+        let request = &server.requests()[0].json();
+        assert_eq!(request["params"][0]["data"], "0x18160ddd");
+    }
+
+    #[test]
+    fn can_read_erc20_allowance() {
+        let owner = Address::from_str("0x1111111111111111111111111111111111111111").unwrap();
+        let spender = Address::from_str("0x2222222222222222222222222222222222222222").unwrap();
+
+        // Only `owner` has approved `spender`, for 500 tokens.
+        let server = MockServer::start(move |request| {
+            let data = request.json()["params"][0]["data"]
+                .as_str()
+                .unwrap()
+                .to_owned();
+            let data = hex::decode(&data[2..]).unwrap();
+            assert_eq!(data[..4], function_selector("allowance(address,address)"));
+
+            let params = [ethabi::ParamType::Address, ethabi::ParamType::Address];
+            let args = ethabi::decode(&params, &data[4..]).unwrap();
+            let approved = args
+                == vec![
+                    ethabi::Token::Address(owner),
+                    ethabi::Token::Address(spender),
+                ];
+            let allowance = if approved { 500_000_000u64 } else { 0 };
+            let result = ethabi::encode(&[ethabi::Token::Uint(allowance.into())]);
+
+            test_util::response(json!(format!("0x{}", hex::encode(result))))
+        });
+        let client = Client::new(server.url());
+        let token = Address::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+
+        let fresh = client.erc20_allowance(token, spender, owner).unwrap();
+        assert_eq!(fresh, U256::zero());
+
+        let set = client.erc20_allowance(token, owner, spender).unwrap();
+        assert_eq!(set, U256::from(500_000_000u64));
+    }
+
+    #[test]
+    fn can_decode_bytes32_token_symbol() {
+        let mut data = b"MKR".to_vec();
+        data.resize(32, 0);
+
+        assert_eq!(decode_string(&Bytes(data)).unwrap(), "MKR");
+    }
+
+    #[test]
+    fn decimals_out_of_range_is_a_parse_error() {
+        let data = Bytes(ethabi::encode(&[ethabi::Token::Uint(256.into())]));
+
+        let res = decode_u8(&data);
+        assert!(matches!(res, Err(ClientError::Parse(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn get_balances_preserves_order() {
+        // Each address has a balance equal to its last byte.
+        let server = MockServer::start(|request| {
+            let address = request.json()["params"][0].as_str().unwrap().to_owned();
+            let last_byte = u8::from_str_radix(&address[40..], 16).unwrap();
+            test_util::response(json!(format!("0x{:x}", last_byte)))
+        });
+        let client = Client::new(server.url());
+
+        let addresses = (1..=10u8)
+            .map(|i| {
+                let mut bytes = [0u8; 20];
+                bytes[19] = i;
+                Address::from_slice(&bytes)
+            })
+            .collect::<Vec<_>>();
+
+        let balances = client
+            .get_balances(&addresses, BlockNumber::Latest, 3)
+            .unwrap();
+
+        let individual = addresses
+            .iter()
+            .map(|address| client.get_balance(*address, BlockNumber::Latest))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let want = (1..=10u64).map(U256::from).collect::<Vec<_>>();
+
+        assert_eq!(balances, want);
+        assert_eq!(individual, want);
+    }
+
+    #[test]
+    fn get_balances_fails_if_any_request_fails() {
+        let server = MockServer::start(|_| test_util::error_response(-32000, "boom"));
+        let client = Client::new(server.url());
+
+        let res = client.get_balances(&[Address::default(); 4], BlockNumber::Latest, 2);
+        assert!(matches!(res, Err(ClientError::JsonRpc(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn get_proof_sends_storage_keys() {
+        let server = MockServer::with_result(json!({
+            "address": "0x0000000000000000000000000000000000000000",
+            "accountProof": ["0xf90211a0"],
+            "balance": "0xde0b6b3a7640000",
+            "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            "nonce": "0x1",
+            "storageHash": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "storageProof": [{ "key": "0x0", "proof": [], "value": "0x0" }]
+        }));
+        let client = Client::new(server.url());
+
+        let proof = client
+            .get_proof(Address::default(), &[H256::zero()], BlockNumber::Latest)
+            .unwrap();
+        assert_eq!(proof.balance, U256::exp10(18));
+        assert_eq!(proof.nonce, U64::from(1));
+        assert!(!proof.account_proof.is_empty());
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["method"], "eth_getProof");
+        assert_eq!(request["params"][1], json!([H256::zero()]));
+        assert_eq!(request["params"][2], "latest");
+    }
+
+    #[test]
+    fn can_create_access_list() {
+        let server = MockServer::with_result(json!({
+            "accessList": [{
+                "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "storageKeys": [
+                    "0x0000000000000000000000000000000000000000000000000000000000000003"
+                ]
+            }],
+            "gasUsed": "0x6b5c"
+        }));
+        let client = Client::new(server.url());
+
+        let request = CallRequest {
+            to: Some(H160::repeat_byte(0xa0)),
+            data: Some(vec![0x70, 0xa0, 0x82, 0x31].into()),
+            ..Default::default()
+        };
+        let (access_list, gas_used) = client
+            .create_access_list(request, BlockNumber::Latest)
+            .unwrap();
+
+        assert_eq!(access_list.len(), 1);
+        assert_eq!(access_list[0].storage_keys, vec![H256::from_low_u64_be(3)]);
+        assert_eq!(gas_used, 0x6b5c);
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["method"], "eth_createAccessList");
+        assert_eq!(request["params"][0]["data"], "0x70a08231");
+    }
+
+    #[test]
+    fn eth_sign_signature_recovers_to_account() {
+        let sk = crate::secret_key_from_hex(
+            "aaaaaaaa6422720fab7fee3f875fc2cb399af859156d8f189b930e485674e472",
+        )
+        .unwrap();
+        let account = crate::address_from_secret_key(&sk);
+
+        // Sign like geth does: EIP-191 hash, recovery id + 27.
+        let server = MockServer::start(move |request| {
+            let data: Bytes = serde_json::from_value(request.json()["params"][1].clone()).unwrap();
+            let hash = crate::hash_personal_message(&data.0);
+            let message = secp256k1::Message::from_slice(&hash).unwrap();
+            let (recovery_id, compact) = secp256k1::Secp256k1::signing_only()
+                .sign_recoverable(&message, &sk)
+                .serialize_compact();
+
+            let mut signature = compact.to_vec();
+            signature.push(u8::try_from(recovery_id.to_i32() + 27).unwrap());
+            test_util::response(json!(Bytes(signature)))
+        });
+        let client = Client::new(server.url());
+
+        let data = Bytes(b"hello".to_vec());
+        let signature = client.eth_sign(account, data.clone()).unwrap();
+
+        let hash = crate::hash_personal_message(&data.0);
+        let recovered = crate::recover_address(&hash, &signature.0).unwrap();
+        assert_eq!(recovered, account);
+    }
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn amounts_use_crate_types() {
+        // Fails to compile if a signature goes back to a `clarity` type.
+        let _: fn(&Client, Address, BlockNumber) -> Result<U256> = Client::get_balance;
+        let _: fn(&Client, Address) -> Result<U256> = Client::get_balance_latest;
+        let _: fn(&Client, Address) -> Result<U256> = Client::get_pending_balance;
+        let _: fn(&Client) -> Result<U256> = Client::gas_price;
+        let _: fn(&Client, U256) -> Result<U256> = Client::gas_price_capped;
+        let _: fn(&Client, CallRequest, BlockNumber) -> Result<U256> = Client::gas_limit;
+
+        // Likewise for addresses.
+        let _: fn(&Client, H160, BlockNumber) -> Result<u64> = Client::get_transaction_count;
+        let _: fn(&Client, H160) -> Result<u64> = Client::get_pending_nonce;
+        let _: fn(&Client, H160, Bytes) -> Result<Bytes> = Client::eth_sign;
+        let _: fn(&Client, H160, H256, BlockNumber) -> Result<H256> = Client::get_storage_at;
+        let _: fn(&Client, H160, BlockNumber) -> Result<Option<H160>> =
+            Client::get_proxy_implementation;
+        let _: fn(&Client, &[H160], BlockNumber, usize) -> Result<Vec<U256>> = Client::get_balances;
+        let _: fn(&Client, H160, H160, H160) -> Result<U256> = Client::erc20_allowance;
+        let _: fn(&Client, &str) -> Result<Option<H160>> = Client::resolve_ens;
+        let _: fn(&Client, H160) -> Result<Option<String>> = Client::lookup_ens;
+        let _: fn(&Client, Vec<(H160, Bytes)>, BlockNumber) -> Result<Vec<Bytes>> =
+            Client::multicall;
+        let _: fn(&Client, &SecretKey, ChainId, Vec<u8>, U256, U256) -> Result<(H256, H160)> =
+            Client::deploy;
+        let _: fn(&Client, &str) -> Result<Option<H160>> = ens::resolve;
+        let _: fn(Client, H160) -> NonceManager = NonceManager::new;
+        let _: fn(&Transaction, ChainId) -> std::result::Result<H160, RecoverSenderError> =
+            Transaction::recover_sender;
+        let _: fn(&SecretKey) -> H160 = address_from_secret_key;
+        let _: fn(&PublicKey) -> H160 = address_from_public_key;
+    }
+
+    #[test]
+    fn gas_price_keeps_all_significant_digits() {
+        let server = MockServer::with_result(json!("0x3b9aca00"));
+        let client = Client::new(server.url());
+
+        let price = client.gas_price().unwrap();
+        assert_eq!(price, U256::exp10(9));
+    }
+
+    #[test]
+    fn gas_price_above_cap_is_rejected() {
+        // 100 gwei.
+        let server = MockServer::with_result(json!("0x174876e800"));
+        let client = Client::new(server.url());
+        let gwei = |n: u64| U256::from(n) * U256::exp10(9);
+
+        let res = client.gas_price_capped(gwei(50));
+        match res {
+            Err(ClientError::GasTooHigh { price, max }) => {
+                assert_eq!(price, gwei(100));
+                assert_eq!(max, gwei(50));
+            }
+            _ => panic!("expected GasTooHigh, got {:?}", res),
+        }
+
+        assert_eq!(client.gas_price_capped(gwei(100)).unwrap(), gwei(100));
+    }
+
+    #[test]
+    fn latest_wrappers_match_explicit_latest() {
+        let server = MockServer::start(|request| {
+            let request = request.json();
+            let result = match (request["method"].as_str(), request["params"][1].as_str()) {
+                (Some("eth_getTransactionCount"), Some("latest")) => "0x5",
+                (Some("eth_getBalance"), Some("latest")) => "0x64",
+                _ => "0x0",
+            };
+            test_util::response(json!(result))
+        });
+        let client = Client::new(server.url());
+        let account = Address::default();
+
+        assert_eq!(
+            client.get_transaction_count_latest(account).unwrap(),
+            client
+                .get_transaction_count(account, BlockNumber::Latest)
+                .unwrap()
+        );
+        assert_eq!(client.get_transaction_count_latest(account).unwrap(), 5);
+        assert_eq!(
+            client.get_balance_latest(account).unwrap(),
+            client.get_balance(account, BlockNumber::Latest).unwrap()
+        );
+        assert_eq!(client.get_balance_latest(account).unwrap(), 100u32.into());
+    }
+
+    #[test]
+    fn pending_wrappers_use_pending_block() {
+        let server = MockServer::start(|request| {
+            let request = request.json();
+            let result = match (request["method"].as_str(), request["params"][1].as_str()) {
+                (Some("eth_getTransactionCount"), Some("pending")) => "0x7",
+                (Some("eth_getTransactionCount"), _) => "0x5",
+                (Some("eth_getBalance"), Some("pending")) => "0x64",
+                _ => "0x0",
+            };
+            test_util::response(json!(result))
+        });
+        let client = Client::new(server.url());
+        let account = Address::default();
+
+        let latest = client
+            .get_transaction_count(account, BlockNumber::Latest)
+            .unwrap();
+        let pending = client.get_pending_nonce(account).unwrap();
+        assert_eq!(pending, 7);
+        assert!(pending >= latest);
+
+        assert_eq!(
+            client.get_pending_balance(account).unwrap(),
+            U256::from(100u64)
+        );
+    }
+
+    #[test]
+    fn transaction_count_larger_than_u32() {
+        let server = MockServer::with_result(json!("0x100000000"));
+        let client = Client::new(server.url());
+
+        let count = client
+            .get_transaction_count(Address::default(), BlockNumber::Latest)
+            .unwrap();
+        assert_eq!(count, u64::from(u32::MAX) + 1);
+    }
+
+    #[test]
+    fn can_get_txpool_status() {
+        let server = MockServer::with_result(json!({ "pending": "0x2", "queued": "0x0" }));
+        let client = Client::new(server.url());
+
+        let status = client.txpool_status().unwrap();
+        assert_eq!(status.pending, U64::from(2));
+        assert_eq!(status.queued, U64::zero());
+        assert_eq!(server.requests()[0].json()["method"], "txpool_status");
+    }
+
+    #[test]
+    fn trace_transaction_calls_selects_call_tracer() {
+        let server = MockServer::with_result(json!({
+            "type": "CALL",
+            "from": "0x0eb44ea45b049fc225cfdf07883dd89c7febd8f0",
+            "to": "0x1111111111111111111111111111111111111111",
+            "value": "0x0",
+            "gas": "0x5208",
+            "gasUsed": "0x5208",
+            "input": "0x"
+        }));
+        let client = Client::new(server.url());
+
+        let trace = client.trace_transaction_calls(H256::zero()).unwrap();
+        assert!(trace.calls.is_empty());
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["method"], "debug_traceTransaction");
+        assert_eq!(request["params"][1], json!({ "tracer": "callTracer" }));
+    }
+
+    /// Answers `eth_blockNumber` with 100 and `eth_getLogs` with the logs in
+    /// the requested range from a fixed set of logs.
+    fn log_node(request: &test_util::RecordedRequest) -> String {
+        let request = request.json();
+        let result = match request["method"].as_str() {
+            Some("eth_blockNumber") => json!("0x64"),
+            Some("eth_getLogs") => {
+                let filter = &request["params"][0];
+                let block = |key: &str| {
+                    u64::from_str_radix(&filter[key].as_str().unwrap()[2..], 16).unwrap()
+                };
+                let (from, to) = (block("fromBlock"), block("toBlock"));
+
+                let logs = [3u64, 10, 11, 50, 99, 100]
+                    .iter()
+                    .filter(|n| (from..=to).contains(*n))
+                    .map(|n| {
+                        json!({
+                            "address": "0x1111111111111111111111111111111111111111",
+                            "topics": [],
+                            "data": "0x",
+                            "blockNumber": format!("0x{:x}", n),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!(logs)
+            }
+            _ => json!(null),
+        };
+        test_util::response(result)
+    }
+
+    #[test]
+    fn streams_large_log_responses() {
+        const LOGS: u64 = 50_000;
+
+        let logs = (0..LOGS)
+            .map(|n| {
+                format!(
+                    r#"{{"address":"0x1111111111111111111111111111111111111111","topics":["0x{:064x}"],"data":"0x{}","blockNumber":"0x{:x}","logIndex":"0x0"}}"#,
+                    n,
+                    "ab".repeat(64),
+                    n
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let body = format!(r#"{{"jsonrpc":"2.0","id":"1","result":[{}]}}"#, logs);
+        let server = MockServer::start(move |_| body.clone());
+        let client = Client::new(server.url());
+
+        let mut count = 0;
+        for (n, log) in (0..).zip(client.get_logs_streaming(Filter::default()).unwrap()) {
+            let log = log.unwrap();
+            assert_eq!(log.block_number(), Some(n));
+            assert_eq!(log.topics(), &[H256::from_low_u64_be(n)]);
+            count += 1;
+        }
+        assert_eq!(count, LOGS);
+        assert_eq!(server.requests()[0].json()["method"], "eth_getLogs");
+    }
+
+    #[test]
+    fn streamed_logs_report_node_errors() {
+        let server = MockServer::start(|_| {
+            test_util::error_response(-32005, "query returned more than 10000 results")
+        });
+        let client = Client::new(server.url());
+
+        let mut logs = client.get_logs_streaming(Filter::default()).unwrap();
+        assert!(matches!(logs.next(), Some(Err(ClientError::JsonRpc(_)))));
+        assert!(logs.next().is_none());
+    }
+
+    #[test]
+    fn filter_changes_include_new_logs() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let emitted = Arc::new(AtomicBool::new(false));
+        let polled = Arc::clone(&emitted);
+        let server = MockServer::start(move |request| {
+            let request = request.json();
+            let result = match request["method"].as_str() {
+                Some("eth_newFilter") => json!("0x9f8e7d"),
+                Some("eth_getFilterChanges") if polled.swap(false, Ordering::SeqCst) => {
+                    json!([{
+                        "address": "0x1111111111111111111111111111111111111111",
+                        "topics": [],
+                        "data": "0x2a",
+                        "blockNumber": "0x65",
+                    }])
+                }
+                Some("eth_getFilterChanges") => json!([]),
+                Some("eth_uninstallFilter") => json!(true),
+                _ => json!(null),
+            };
+            test_util::response(result)
+        });
+        let client = Client::new(server.url());
+
+        let filter = FilterBuilder::default()
+            .address(vec![H160::repeat_byte(0x11)])
+            .build();
+        let id = client.new_filter(filter).unwrap();
+        assert_eq!(id, FilterId("0x9f8e7d".to_string()));
+        assert!(client.get_filter_changes(&id).unwrap().is_empty());
+
+        emitted.store(true, Ordering::SeqCst);
+        let changes = client.get_filter_changes(&id).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].data, Bytes(vec![0x2a]));
+        assert!(client.get_filter_changes(&id).unwrap().is_empty());
+
+        assert!(client.uninstall_filter(id).unwrap());
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].json()["params"][0]["address"],
+            "0x1111111111111111111111111111111111111111"
+        );
+        assert_eq!(requests[1].json()["params"], json!(["0x9f8e7d"]));
+        assert_eq!(requests[4].json()["params"], json!(["0x9f8e7d"]));
+    }
+
+    #[test]
+    fn get_logs_with_timeout_waits_for_slow_node() {
+        let server = MockServer::start(|request| {
+            std::thread::sleep(Duration::from_millis(50));
+            log_node(request)
+        });
+        let client = Client::new(server.url());
+        let filter = FilterBuilder::default()
+            .set_from_block(0.into())
+            .set_to_block(10.into())
+            .build();
+
+        let logs = client
+            .get_logs_with_timeout(filter, Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(server.requests()[0].json()["method"], "eth_getLogs");
+    }
+
+    #[test]
+    fn get_logs_with_timeout_gives_up() {
+        let server = MockServer::start(|request| {
+            std::thread::sleep(Duration::from_millis(500));
+            log_node(request)
+        });
+        let client = Client::new(server.url());
+        let filter = FilterBuilder::default()
+            .set_from_block(0.into())
+            .set_to_block(10.into())
+            .build();
+
+        let res = client.get_logs_with_timeout(filter, Duration::from_millis(50));
+        assert!(matches!(res, Err(ClientError::Transport(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn chunked_logs_match_unchunked_query() {
+        let server = MockServer::start(log_node);
+        let client = Client::new(server.url());
+
+        let unchunked = client
+            .get_logs(
+                FilterBuilder::default()
+                    .set_from_block(0.into())
+                    .set_to_block(100.into())
+                    .build(),
+            )
+            .unwrap();
+        let _ = server.requests();
+
+        let filter = FilterBuilder::default()
+            .set_from_block(BlockNumber::Earliest)
+            .set_to_block(BlockNumber::Latest)
+            .build();
+        let chunked = client.get_logs_chunked(filter, 10).unwrap();
+
+        assert_eq!(chunked.len(), 6);
+        assert_eq!(chunked, unchunked);
+
+        // One eth_blockNumber then 11 windows: 0-9, 10-19, ..., 90-99, 100-100.
+        let requests = server.requests();
+        assert_eq!(requests[0].json()["method"], "eth_blockNumber");
+        assert_eq!(requests.len(), 12);
+        assert_eq!(requests[11].json()["params"][0]["fromBlock"], "0x64");
+        assert_eq!(requests[11].json()["params"][0]["toBlock"], "0x64");
+    }
+
+    #[test]
+    fn bisecting_logs_covers_whole_range() {
+        // Refuses any query wider than 8 blocks.
+        let server = MockServer::start(|request| {
+            let json = request.json();
+            if json["method"] == "eth_getLogs" {
+                let filter = &json["params"][0];
+                let block = |key: &str| {
+                    u64::from_str_radix(&filter[key].as_str().unwrap()[2..], 16).unwrap()
+                };
+                if block("toBlock") - block("fromBlock") >= 8 {
+                    return test_util::error_response(
+                        -32005,
+                        "query returned more than 10000 results",
+                    );
+                }
+            }
+            log_node(request)
+        });
+        let client = Client::new(server.url());
+
+        let filter = FilterBuilder::default()
+            .set_from_block(0.into())
+            .set_to_block(100.into())
+            .build();
+
+        let err = client.get_logs_chunked(filter.clone(), 50).unwrap_err();
+        assert!(matches!(err, ClientError::JsonRpc(ref e) if e.is_result_limit()));
+
+        let logs = client.get_logs_chunked_bisecting(filter, 50).unwrap();
+        let blocks = logs
+            .iter()
+            .map(|log| log.block_number.unwrap().as_u64())
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![3, 10, 11, 50, 99, 100]);
+    }
+
+    #[test]
+    fn block_iter_fetches_each_block_in_range() {
+        let server = MockServer::start(|request| {
+            let json = request.json();
+            match json["method"].as_str() {
+                Some("eth_blockNumber") => test_util::response(json!("0x5")),
+                _ => {
+                    let mut block = block_json(json!([]));
+                    block["number"] = json["params"][0].clone();
+                    test_util::response(block)
+                }
+            }
+        });
+        let client = Client::new(server.url());
+
+        let numbers = client
+            .block_iter::<H256>(2.into(), BlockNumber::Latest, false)
+            .unwrap()
+            .map(|block| block.unwrap().number.unwrap().as_u64())
+            .collect::<Vec<_>>();
+
+        assert_eq!(numbers, vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn simulate_returns_output_and_gas() {
+        let server = MockServer::start(|request| match request.json()["method"].as_str() {
+            Some("eth_call") => test_util::response(json!("0x01")),
+            _ => test_util::response(json!("0x5208")),
+        });
+        let client = Client::new(server.url());
+
+        let result = client
+            .simulate(CallRequest::default(), BlockNumber::Latest, None)
+            .unwrap();
+        assert_eq!(result.output, Bytes(vec![1]));
+        assert_eq!(result.gas, 21_000);
+
+        let methods = server
+            .requests()
+            .iter()
+            .map(|request| request.json()["method"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(methods, vec!["eth_call", "eth_estimateGas"]);
+    }
+
+    #[test]
+    fn simulate_surfaces_revert_reason() {
+        let mut data = vec![0x08, 0xc3, 0x79, 0xa0];
+        data.extend(ethabi::encode(&[ethabi::Token::String(
+            "insufficient allowance".to_owned(),
+        )]));
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "error": {
+                "code": 3,
+                "message": "execution reverted",
+                "data": format!("0x{}", hex::encode(data)),
+            },
+        })
+        .to_string();
+        let server = MockServer::start(move |_| body.clone());
+        let client = Client::new(server.url());
+
+        match client.simulate(CallRequest::default(), BlockNumber::Latest, None) {
+            Err(ClientError::JsonRpc(e)) => assert_eq!(e.revert_reason(), "insufficient allowance"),
+            res => panic!("expected a revert, got {:?}", res),
+        }
+        // Gas is not estimated for a reverting call.
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    /// Answers `aggregate3` calls of `balanceOf(address)`, each account's
+    /// balance is its first address byte.
+    fn multicall_node(request: &test_util::RecordedRequest) -> String {
+        let json = request.json();
+        let data = hex::decode(&json["params"][0]["data"].as_str().unwrap()[2..]).unwrap();
+        assert_eq!(data[..4], function_selector(AGGREGATE3_SIGNATURE));
+
+        let call_type = ethabi::ParamType::Array(Box::new(ethabi::ParamType::Tuple(vec![
+            ethabi::ParamType::Address,
+            ethabi::ParamType::Bool,
+            ethabi::ParamType::Bytes,
+        ])));
+        let calls = match ethabi::decode(&[call_type], &data[4..]).unwrap().remove(0) {
+            ethabi::Token::Array(calls) => calls,
+            token => panic!("unexpected token: {:?}", token),
+        };
+
+        let results = calls
+            .into_iter()
+            .map(|call| {
+                let data = match call {
+                    ethabi::Token::Tuple(mut fields) => fields.remove(2).into_bytes().unwrap(),
+                    token => panic!("unexpected token: {:?}", token),
+                };
+                assert_eq!(data[..4], function_selector("balanceOf(address)"));
+
+                // Address is right aligned in the 32 byte word.
+                let balance = ethabi::encode(&[ethabi::Token::Uint(data[4 + 12].into())]);
+                ethabi::Token::Tuple(vec![
+                    ethabi::Token::Bool(true),
+                    ethabi::Token::Bytes(balance),
+                ])
+            })
+            .collect();
+        let result = ethabi::encode(&[ethabi::Token::Array(results)]);
+
+        test_util::response(json!(format!("0x{}", hex::encode(result))))
+    }
+
+    #[test]
+    fn multicall_batches_calls_into_one_request() {
+        let server = MockServer::start(multicall_node);
+        let client = Client::new(server.url());
+
+        let token = Address::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+        let balance_of = |account: &str| {
+            let mut data = function_selector("balanceOf(address)").to_vec();
+            data.extend(ethabi::encode(&[ethabi::Token::Address(
+                account.parse().unwrap(),
+            )]));
+            (token, Bytes(data))
+        };
+
+        let results = client
+            .multicall(
+                vec![
+                    balance_of("0x0a00000000000000000000000000000000000000"),
+                    balance_of("0x1400000000000000000000000000000000000000"),
+                ],
+                BlockNumber::Latest,
+            )
+            .unwrap();
+
+        let balances = results
+            .iter()
+            .map(|data| U256::from_big_endian(&data.0))
+            .collect::<Vec<_>>();
+        assert_eq!(balances, vec![U256::from(10), U256::from(20)]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].json()["params"][0]["to"],
+            MULTICALL3_ADDRESS.to_lowercase()
+        );
+    }
+
+    #[test]
+    fn raw_request_matches_modeled_method() {
+        let version = "Geth/v1.10.2-unstable-f304290b-20210323/linux-amd64/go1.13.8";
+        let server = MockServer::with_result(json!(version));
+        let client = Client::new(server.url());
+
+        let raw: String = client
+            .raw_request("web3_clientVersion", Vec::<()>::new())
+            .unwrap();
+        assert_eq!(raw, client.client_version().unwrap());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].json(), requests[1].json());
+    }
+
+    #[test]
+    fn call_sends_state_overrides() {
+        let rich: H160 = "0x0eb44ea45b049fc225cfdf07883dd89c7febd8f0"
+            .parse()
+            .unwrap();
+
+        // Reverts unless the caller's balance is overridden.
+        let server = MockServer::start(|request| {
+            let params = &request.json()["params"];
+            let from = params[0]["from"].as_str().unwrap().to_owned();
+            if params[2][&from]["balance"].is_string() {
+                test_util::response(json!("0x01"))
+            } else {
+                test_util::error_response(3, "execution reverted")
+            }
+        });
+        let client = Client::new(server.url());
+        let request = CallRequest {
+            from: Some(rich),
+            ..Default::default()
+        };
+
+        let err = client
+            .call(request.clone(), BlockNumber::Latest, None)
+            .unwrap_err();
+        assert!(matches!(err, ClientError::JsonRpc(_)));
+        assert_eq!(
+            server.requests()[0].json()["params"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let mut overrides = StateOverride::new();
+        overrides.insert(rich, AccountOverride {
+            balance: Some(U256::exp10(18)),
+            ..Default::default()
+        });
+        let output = client
+            .call(request, BlockNumber::Latest, Some(overrides))
+            .unwrap();
+        assert_eq!(output, Bytes(vec![1]));
+    }
+
+    fn receipt_json(index: u64) -> serde_json::Value {
+        json!({
+            "transactionHash": format!("0x{:064x}", index + 1),
+            "transactionIndex": format!("0x{:x}", index),
+            "blockHash": "0x83eaba432089a0bfe99e9fc9022d1cfcb78f95f407821be81737c84ae0b439c5",
+            "blockNumber": "0x1b4",
+            "cumulativeGasUsed": "0x5208",
+            "gasUsed": "0x5208",
+            "contractAddress": null,
+            "logs": [],
+            "status": "0x1",
+            "root": null,
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+        })
+    }
+
+    /// Answers a batch with a nonce of 7, a gas price of 1 gwei and a gas
+    /// estimate of 21000.
+    fn prepare_node(request: &test_util::RecordedRequest) -> String {
+        let responses: Vec<_> = request
+            .json()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|request| {
+                let result = match request["method"].as_str() {
+                    Some("eth_getTransactionCount") => "0x7",
+                    Some("eth_gasPrice") => "0x3b9aca00",
+                    Some("eth_estimateGas") => "0x5208",
+                    _ => return json!({ "jsonrpc": "2.0", "id": request["id"], "result": null }),
+                };
+                json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+            })
+            .collect();
+        serde_json::to_string(&responses).unwrap()
+    }
+
+    #[test]
+    fn prepare_eip1559_transaction_uses_fee_strategy() {
+        let server = MockServer::start(prepare_node);
+        let client = Client::new(server.url());
+        let gwei = U256::exp10(9);
+        let fees = FixedFee(GasSuggestion {
+            max_fee_per_gas: gwei * 30,
+            max_priority_fee_per_gas: gwei * 2,
+        });
+
+        let tx = client
+            .prepare_eip1559_transaction(
+                CallRequest {
+                    from: Some(H160::repeat_byte(0x11)),
+                    to: Some(H160::repeat_byte(0x22)),
+                    value: Some(U256::exp10(18)),
+                    ..Default::default()
+                },
+                ChainId::MAINNET,
+                &fees,
+            )
+            .unwrap();
+
+        assert_eq!(tx, Eip1559Transaction {
+            chain_id: ChainId::MAINNET,
+            nonce: 7.into(),
+            max_priority_fee_per_gas: gwei * 2,
+            max_fee_per_gas: gwei * 30,
+            gas_limit: 21_000.into(),
+            to: Some(H160::repeat_byte(0x22)),
+            value: U256::exp10(18),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn prepare_transaction_batches_all_three_requests() {
+        let server = MockServer::start(prepare_node);
+        let client = Client::new(server.url());
+
+        let from = H160::repeat_byte(0x11);
+        let params = client
+            .prepare_transaction(CallRequest {
+                from: Some(from),
+                to: Some(H160::repeat_byte(0x22)),
+                value: Some(U256::exp10(18)),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(params, TxParams {
+            nonce: 7,
+            gas_price: U256::exp10(9),
+            gas_limit: U256::from(21_000),
+        });
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let batch = requests[0].json();
+        assert_eq!(batch[0]["method"], "eth_getTransactionCount");
+        assert_eq!(batch[0]["params"], json!([from, "pending"]));
+        assert_eq!(batch[1]["method"], "eth_gasPrice");
+        assert_eq!(batch[2]["method"], "eth_estimateGas");
+        assert_eq!(batch[2]["params"][0]["to"], json!(H160::repeat_byte(0x22)));
+    }
+
+    #[test]
+    fn prepare_transaction_requires_sender() {
+        let server = MockServer::with_result(json!("0x0"));
+        let client = Client::new(server.url());
+
+        let res = client.prepare_transaction(CallRequest::default());
+        assert!(
+            matches!(res, Err(ClientError::InvalidRequest(_))),
+            "{:?}",
+            res
+        );
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn can_get_receipts_in_one_batch() {
+        let server = MockServer::start(|request| {
+            let responses: Vec<_> = request
+                .json()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|request| {
+                    let hash = request["params"][0].as_str().unwrap();
+                    let index = u64::from_str_radix(&hash[2..], 16).unwrap();
+                    // Only odd transactions have been mined.
+                    let receipt = if index % 2 == 1 {
+                        receipt_json(index - 1)
+                    } else {
+                        json!(null)
+                    };
+                    json!({ "jsonrpc": "2.0", "id": request["id"], "result": receipt })
+                })
+                .collect();
+            serde_json::to_string(&responses).unwrap()
+        });
+        let client = Client::new(server.url());
+
+        let hashes: Vec<_> = (1..=4).map(H256::from_low_u64_be).collect();
+        let receipts = client.get_transaction_receipts(&hashes).unwrap();
+
+        let found: Vec<_> = receipts
+            .iter()
+            .map(|receipt| receipt.as_ref().map(|r| r.transaction_hash))
+            .collect();
+        assert_eq!(found, vec![Some(hashes[0]), None, Some(hashes[2]), None]);
+        assert_eq!(server.requests().len(), 1);
+
+        assert!(client.get_transaction_receipts(&[]).unwrap().is_empty());
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn block_receipts_match_transaction_count() {
+        let server = MockServer::start(|request| {
+            let result = match request.json()["method"].as_str() {
+                Some("eth_getBlockReceipts") => json!((0..3).map(receipt_json).collect::<Vec<_>>()),
+                Some("eth_getBlockTransactionCountByNumber") => json!("0x3"),
+                _ => json!(null),
+            };
+            test_util::response(result)
+        });
+        let client = Client::new(server.url());
+        let block = BlockNumber::from(436);
+
+        let receipts = client.get_block_receipts(block).unwrap().unwrap();
+        let count = client.get_block_transaction_count(block).unwrap();
+        assert_eq!(receipts.len() as u64, count);
+        assert_eq!(receipts[2].transaction_index, 2.into());
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["method"], "eth_getBlockReceipts");
+        assert_eq!(request["params"], json!(["0x1b4"]));
+    }
+
+    #[test]
+    fn block_receipts_of_unknown_block_is_none() {
+        let server = MockServer::with_result(json!(null));
+        let client = Client::new(server.url());
+
+        let receipts = client.get_block_receipts(BlockNumber::from(436)).unwrap();
+        assert!(receipts.is_none());
+    }
+
+    #[test]
+    fn poll_receipt_waits_for_receipt() {
+        let polls = std::sync::atomic::AtomicUsize::new(0);
+        let server = MockServer::start(move |_| {
+            let n = polls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            test_util::response(if n < 2 { json!(null) } else { receipt_json(0) })
+        });
+        let client = Client::new(server.url());
+        let hash = H256::from_low_u64_be(1);
+
+        let receipt = client
+            .poll_receipt(hash, Duration::from_millis(1), Duration::from_secs(5))
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipt.transaction_hash, hash);
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn poll_receipt_without_deadline() {
+        let polls = std::sync::atomic::AtomicUsize::new(0);
+        let server = MockServer::start(move |_| {
+            let n = polls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            test_util::response(if n < 1 { json!(null) } else { receipt_json(0) })
+        });
+        let client = Client::new(server.url());
+
+        let receipt = client
+            .poll_receipt(
+                H256::from_low_u64_be(1),
+                Duration::from_millis(1),
+                Duration::MAX,
+            )
+            .unwrap();
+        assert!(receipt.is_some());
+    }
+
+    #[test]
+    fn poll_receipt_returns_none_on_timeout() {
+        let server = MockServer::with_result(json!(null));
+        let client = Client::new(server.url());
+
+        let receipt = client
+            .poll_receipt(
+                H256::zero(),
+                Duration::from_millis(1),
+                Duration::from_millis(30),
+            )
+            .unwrap();
+        assert!(receipt.is_none());
+
+        // Backoff of 1, 2, 4, 8, 8... ms means far fewer than 30 polls.
+        let polls = server.requests().len();
+        assert!((2..15).contains(&polls), "polls: {}", polls);
+    }
+
+    #[test]
+    fn deploy_returns_create_address() {
+        let server = MockServer::start(|request| {
+            let result = match request.json()["method"].as_str() {
+                Some("eth_getTransactionCount") => json!("0x1"),
+                Some("eth_sendRawTransaction") => json!(format!("0x{:064x}", 1)),
+                _ => json!(null),
+            };
+            test_util::response(result)
+        });
+        let client = Client::new(server.url());
+        let sk = crate::secret_key_from_hex(&"46".repeat(32)).unwrap();
+        let sender = address_from_secret_key(&sk);
+
+        let (hash, contract) = client
+            .deploy(
+                &sk,
+                ChainId::MAINNET,
+                vec![0x60, 0x80],
+                U256::exp10(9),
+                100_000.into(),
+            )
+            .unwrap();
+
+        assert_eq!(hash, H256::from_low_u64_be(1));
+        let want = contract_address(sender, 1);
+        assert_eq!(contract.as_bytes(), want.as_bytes());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].json()["params"][1], "pending");
+        let raw = Bytes::from_hex(requests[1].json()["params"][0].as_str().unwrap()).unwrap();
+        let decoded = crate::rlp::decode(&raw.0).unwrap();
+        let fields = decoded.as_list().unwrap();
+        // Nonce 1, no recipient.
+        assert_eq!(fields[0].as_bytes(), Some(&[1u8][..]));
+        assert_eq!(fields[3].as_bytes(), Some(&[][..]));
+    }
+
+    #[test]
+    fn send_raw_transaction_rejects_malformed_hex() {
+        let server = MockServer::with_result(json!(format!("0x{:064x}", 1)));
+        let client = Client::new(server.url());
+
+        for raw in &["f86c09", "0xf86", "0x", "0xzz"] {
+            let err = client.send_raw_transaction((*raw).to_owned()).unwrap_err();
+            assert!(
+                matches!(err, ClientError::InvalidRequest(_)),
+                "{}: {:?}",
+                raw,
+                err
+            );
+        }
+        assert!(server.requests().is_empty());
+
+        let err = client.send_raw_transaction("0xf86".to_owned()).unwrap_err();
+        assert!(
+            err.to_string().contains("odd number of hex digits"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn send_raw_transaction_bytes_encodes_hex() {
+        let server = MockServer::with_result(json!(format!("0x{:064x}", 1)));
+        let client = Client::new(server.url());
+
+        let hash = client.send_raw_transaction_bytes(&[0xf8, 0x6c]).unwrap();
+        assert_eq!(hash, H256::from_low_u64_be(1));
+        assert_eq!(server.requests()[0].json()["params"], json!(["0xf86c"]));
+    }
+}
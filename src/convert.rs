@@ -0,0 +1,72 @@
+//! Conversions between the `clarity` types used for signing and the
+//! `ethereum-types` types used by the JSON-RPC API.
+
+use clarity::{Address, Uint256};
+
+use crate::types::{H160, U256};
+
+/// Converts an `H160` to a `clarity::Address`.
+pub fn to_clarity_address(address: H160) -> Address {
+    Address::from_slice(address.as_bytes()).expect("address is 20 bytes")
+}
+
+/// Converts a `clarity::Address` to an `H160`.
+pub fn from_clarity_address(address: &Address) -> H160 {
+    H160::from_slice(address.as_bytes())
+}
+
+/// Converts a `U256` to a `clarity::Uint256`.
+pub fn to_clarity_uint(n: U256) -> Uint256 {
+    let mut bytes = [0u8; 32];
+    n.to_big_endian(&mut bytes);
+    Uint256::from_bytes_be(&bytes)
+}
+
+/// Converts a `clarity::Uint256` to a `U256`, returns `None` if arithmetic
+/// took `n` past 256 bits.
+pub fn from_clarity_uint(n: &Uint256) -> Option<U256> {
+    let bytes = n.to_bytes_be();
+    if bytes.len() > 32 {
+        return None;
+    }
+
+    Some(U256::from_big_endian(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_round_trips() {
+        let address: Address = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+            .parse()
+            .unwrap();
+        let h160 = from_clarity_address(&address);
+
+        assert_eq!(
+            h160,
+            "0xd8da6bf26964af9d7eed9e03e53415d37aa96045"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(to_clarity_address(h160), address);
+    }
+
+    #[test]
+    fn uint_round_trips() {
+        for n in [U256::zero(), U256::from(1_000_000_007u64), U256::MAX] {
+            let uint = to_clarity_uint(n);
+            assert_eq!(uint.to_string(), n.to_string());
+            assert_eq!(from_clarity_uint(&uint), Some(n));
+        }
+    }
+
+    #[test]
+    fn uint_past_256_bits_does_not_convert() {
+        let n = to_clarity_uint(U256::MAX);
+        let overflowed = Uint256(n.0.clone() + n.0);
+
+        assert_eq!(from_clarity_uint(&overflowed), None);
+    }
+}
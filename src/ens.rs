@@ -0,0 +1,236 @@
+//! Resolves Ethereum Name Service (ENS) names to addresses.
+//!
+//! ref: https://docs.ens.domains/contract-api-reference/name-processing
+
+use crate::api::{Client, ClientError};
+use crate::function_selector;
+use crate::types::{Address, BlockNumber, Bytes, CallRequest, H160, H256};
+
+/// Address of the ENS registry, the same on mainnet and the ENS testnets.
+pub const ENS_REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+/// Returns the namehash of `name`, the node ENS contracts identify it by.
+///
+/// `name` must already be normalized (e.g. lowercase), no normalization is
+/// done here.
+pub fn namehash(name: &str) -> H256 {
+    let mut node = [0u8; 32];
+    if name.is_empty() {
+        return H256::from(node);
+    }
+
+    for label in name.rsplit('.') {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&node);
+        bytes[32..].copy_from_slice(&crate::keccak256(label.as_bytes()));
+        node = crate::keccak256(&bytes);
+    }
+
+    H256::from(node)
+}
+
+/// Resolves `name` to an address using the registry's resolver for it.
+/// Return `None` if the name has no resolver or the resolver has no address
+/// for it.
+pub fn resolve(client: &Client, name: &str) -> Result<Option<Address>, ClientError> {
+    let node = namehash(name);
+    let registry: H160 = ENS_REGISTRY_ADDRESS
+        .parse()
+        .expect("registry address is valid hex");
+
+    let resolver = match call_address(client, registry, "resolver(bytes32)", node)? {
+        Some(resolver) => resolver,
+        None => return Ok(None),
+    };
+    let address = call_address(client, resolver, "addr(bytes32)", node)?;
+
+    Ok(address)
+}
+
+/// Returns the node of the reverse record of `address`, i.e. the namehash of
+/// `<address in lowercase hex>.addr.reverse`.
+pub fn reverse_node(address: &Address) -> H256 {
+    namehash(&format!("{}.addr.reverse", hex::encode(address.as_bytes())))
+}
+
+/// Looks up the primary name of `address` in its reverse record. Return
+/// `None` if there is no reverse record, or if the name does not resolve
+/// back to `address` since anyone can claim any name in their reverse
+/// record.
+pub fn lookup(client: &Client, address: &Address) -> Result<Option<String>, ClientError> {
+    let node = reverse_node(address);
+    let registry: H160 = ENS_REGISTRY_ADDRESS
+        .parse()
+        .expect("registry address is valid hex");
+
+    let resolver = match call_address(client, registry, "resolver(bytes32)", node)? {
+        Some(resolver) => resolver,
+        None => return Ok(None),
+    };
+    let output = call(client, resolver, "name(bytes32)", node)?;
+    let tokens =
+        ethabi::decode(&[ethabi::ParamType::String], &output.0).map_err(ClientError::parse)?;
+    let name = match tokens.into_iter().next() {
+        Some(ethabi::Token::String(name)) if name.is_empty() => return Ok(None),
+        Some(ethabi::Token::String(name)) => name,
+        _ => return Err(ClientError::parse(ethabi::Error::InvalidData)),
+    };
+
+    match resolve(client, &name)? {
+        Some(resolved) if resolved == *address => Ok(Some(name)),
+        _ => Ok(None),
+    }
+}
+
+/// Calls the function `signature`, taking a single `bytes32`, on `contract`.
+fn call(
+    client: &Client,
+    contract: H160,
+    signature: &str,
+    node: H256,
+) -> Result<Bytes, ClientError> {
+    let mut data = function_selector(signature).to_vec();
+    data.extend_from_slice(node.as_bytes());
+    let request = CallRequest {
+        to: Some(contract),
+        data: Some(Bytes(data)),
+        ..Default::default()
+    };
+
+    client.call(request, BlockNumber::Latest, None)
+}
+
+/// Like `call` but decodes the returned `address`. Return `None` for the
+/// zero address.
+fn call_address(
+    client: &Client,
+    contract: H160,
+    signature: &str,
+    node: H256,
+) -> Result<Option<H160>, ClientError> {
+    let output = call(client, contract, signature, node)?;
+
+    let tokens =
+        ethabi::decode(&[ethabi::ParamType::Address], &output.0).map_err(ClientError::parse)?;
+    match tokens.into_iter().next() {
+        Some(ethabi::Token::Address(address)) if address.is_zero() => Ok(None),
+        Some(ethabi::Token::Address(address)) => Ok(Some(address)),
+        _ => Err(ClientError::parse(ethabi::Error::InvalidData)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::{self, MockServer};
+
+    const RESOLVER: &str = "0x4976fb03c32e5b8cfe2b6ccb31c09ba78ebaba41";
+    const VITALIK: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    // Claims to be vitalik.eth in its reverse record.
+    const SPOOFER: &str = "0x1111111111111111111111111111111111111111";
+
+    #[test]
+    fn namehash_matches_known_nodes() {
+        assert_eq!(namehash(""), H256::zero());
+        assert_eq!(
+            namehash("eth"),
+            "0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            namehash("vitalik.eth"),
+            "0xee6c4522aab0003e8d14cd40a6af439055fd2577951148c14b6cea9a53475835"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn reverse_node_uses_lowercase_address() {
+        let address = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            reverse_node(&address),
+            namehash("d8da6bf26964af9d7eed9e03e53415d37aa96045.addr.reverse")
+        );
+    }
+
+    /// Mainnet ENS, with only `vitalik.eth` registered and reverse records
+    /// for `VITALIK` and `SPOOFER`.
+    fn ens_node(request: &test_util::RecordedRequest) -> String {
+        let params = &request.json()["params"][0];
+        let to = params["to"].as_str().unwrap().to_lowercase();
+        let data = hex::decode(&params["data"].as_str().unwrap()[2..]).unwrap();
+        let (selector, node) = data.split_at(4);
+
+        let reverse_records: Vec<H256> = [VITALIK, SPOOFER]
+            .iter()
+            .map(|address| reverse_node(&address.parse().unwrap()))
+            .collect();
+        let is_registered = node == namehash("vitalik.eth").as_bytes()
+            || reverse_records.iter().any(|r| r.as_bytes() == node);
+
+        let token = if !is_registered {
+            ethabi::Token::Address(H160::zero())
+        } else if to == ENS_REGISTRY_ADDRESS.to_lowercase()
+            && selector == function_selector("resolver(bytes32)")
+        {
+            ethabi::Token::Address(RESOLVER.parse().unwrap())
+        } else if to == RESOLVER && selector == function_selector("addr(bytes32)") {
+            ethabi::Token::Address(VITALIK.parse().unwrap())
+        } else if to == RESOLVER && selector == function_selector("name(bytes32)") {
+            ethabi::Token::String("vitalik.eth".to_owned())
+        } else {
+            return test_util::error_response(3, "execution reverted");
+        };
+        let result = ethabi::encode(&[token]);
+
+        test_util::response(json!(format!("0x{}", hex::encode(result))))
+    }
+
+    #[test]
+    fn can_resolve_registered_name() {
+        let server = MockServer::start(ens_node);
+        let client = Client::new(server.url());
+
+        let address = client.resolve_ens("vitalik.eth").unwrap();
+        assert_eq!(address, Some(VITALIK.parse().unwrap()));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn unregistered_name_resolves_to_none() {
+        let server = MockServer::start(ens_node);
+        let client = Client::new(server.url());
+
+        assert_eq!(client.resolve_ens("not-registered.eth").unwrap(), None);
+        // No resolver, so the resolver is never called.
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn can_lookup_primary_name() {
+        let server = MockServer::start(ens_node);
+        let client = Client::new(server.url());
+
+        let name = client.lookup_ens(VITALIK.parse().unwrap()).unwrap();
+        assert_eq!(name.as_deref(), Some("vitalik.eth"));
+    }
+
+    #[test]
+    fn lookup_rejects_spoofed_reverse_record() {
+        let server = MockServer::start(ens_node);
+        let client = Client::new(server.url());
+
+        assert_eq!(client.lookup_ens(SPOOFER.parse().unwrap()).unwrap(), None);
+        let no_record = "0x2222222222222222222222222222222222222222"
+            .parse()
+            .unwrap();
+        assert_eq!(client.lookup_ens(no_record).unwrap(), None);
+    }
+}
@@ -0,0 +1,447 @@
+//! Suggests slow, standard and fast gas prices from recent blocks, tracks a
+//! smoothed gas price for long running senders, and lets callers plug in a
+//! `FeeStrategy` when building transactions.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::api::{Client, ClientError};
+use crate::types::{BlockNumber, U256};
+
+/// Number of recent blocks sampled by default.
+const DEFAULT_BLOCK_COUNT: u64 = 10;
+
+/// Default reward percentiles of the slow, standard and fast tiers.
+const DEFAULT_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+
+/// Suggests EIP-1559 fees from the priority fees paid in recent blocks
+/// (`eth_feeHistory`), each tier uses the average of a reward percentile.
+///
+/// Nodes that do not support `eth_feeHistory` get `eth_gasPrice` for all
+/// tiers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GasOracle {
+    block_count: u64,
+    percentiles: [f64; 3],
+}
+
+/// Fees to use for a transaction, in wei.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasSuggestion {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// Suggested fees, from cheapest to quickest to be included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasSuggestions {
+    pub slow: GasSuggestion,
+    pub standard: GasSuggestion,
+    pub fast: GasSuggestion,
+}
+
+impl Default for GasOracle {
+    fn default() -> Self {
+        GasOracle {
+            block_count: DEFAULT_BLOCK_COUNT,
+            percentiles: DEFAULT_PERCENTILES,
+        }
+    }
+}
+
+impl GasOracle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Samples the last `block_count` blocks.
+    pub fn with_block_count(mut self, block_count: u64) -> Self {
+        self.block_count = block_count.max(1);
+        self
+    }
+
+    /// Uses the given reward percentiles (0 to 100) for the slow, standard
+    /// and fast tiers.
+    pub fn with_percentiles(mut self, slow: f64, standard: f64, fast: f64) -> Self {
+        self.percentiles = [slow, standard, fast];
+        self
+    }
+
+    /// Returns fee suggestions for the next block.
+    pub fn estimate(&self, client: &Client) -> Result<GasSuggestions, ClientError> {
+        match fee_history_suggestions(client, self.block_count, &self.percentiles)? {
+            Some(tiers) => Ok(GasSuggestions {
+                slow: tiers[0],
+                standard: tiers[1],
+                fast: tiers[2],
+            }),
+            None => legacy_suggestions(client),
+        }
+    }
+}
+
+/// A policy for choosing the fees of a transaction, see
+/// `Client::prepare_eip1559_transaction`.
+pub trait FeeStrategy {
+    /// Returns the fees to use for a transaction sent now.
+    fn fees(&self, client: &Client) -> Result<GasSuggestion, ClientError>;
+}
+
+/// Uses the standard tier.
+impl FeeStrategy for GasOracle {
+    fn fees(&self, client: &Client) -> Result<GasSuggestion, ClientError> {
+        Ok(self.estimate(client)?.standard)
+    }
+}
+
+/// Always uses the same fees, without asking the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedFee(pub GasSuggestion);
+
+impl FixedFee {
+    /// Pays `gas_price` per gas whatever the base fee, like a legacy
+    /// transaction.
+    pub fn gas_price(gas_price: U256) -> Self {
+        FixedFee(GasSuggestion {
+            max_fee_per_gas: gas_price,
+            max_priority_fee_per_gas: gas_price,
+        })
+    }
+}
+
+impl FeeStrategy for FixedFee {
+    fn fees(&self, _: &Client) -> Result<GasSuggestion, ClientError> {
+        Ok(self.0)
+    }
+}
+
+/// Tips the average of a single reward percentile (0 to 100) over recent
+/// blocks, e.g. 90 to be included ahead of most transactions.
+///
+/// Nodes that do not support `eth_feeHistory` get `eth_gasPrice`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Percentile {
+    percentile: f64,
+    block_count: u64,
+}
+
+impl Percentile {
+    pub fn new(percentile: f64) -> Self {
+        Percentile {
+            percentile,
+            block_count: DEFAULT_BLOCK_COUNT,
+        }
+    }
+
+    /// Samples the last `block_count` blocks.
+    pub fn with_block_count(mut self, block_count: u64) -> Self {
+        self.block_count = block_count.max(1);
+        self
+    }
+}
+
+impl FeeStrategy for Percentile {
+    fn fees(&self, client: &Client) -> Result<GasSuggestion, ClientError> {
+        match fee_history_suggestions(client, self.block_count, &[self.percentile])? {
+            Some(fees) => Ok(fees[0]),
+            None => Ok(legacy_suggestions(client)?.standard),
+        }
+    }
+}
+
+/// How long a `GasTracker` sample is used for by default.
+const DEFAULT_TTL: Duration = Duration::from_secs(15);
+
+/// Tracks an exponential moving average of `eth_gasPrice`, so many callers
+/// can ask for a gas price without each hitting the node.
+///
+/// The node is only queried when the last sample is older than the TTL,
+/// each new sample is given a weight of one quarter.
+#[derive(Debug)]
+pub struct GasTracker {
+    client: Client,
+    ttl: Duration,
+    state: Mutex<Option<Sample>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    at: Instant,
+    average: U256,
+}
+
+impl GasTracker {
+    pub fn new(client: Client) -> Self {
+        GasTracker {
+            client,
+            ttl: DEFAULT_TTL,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Sets how long a sample is used before the node is queried again,
+    /// defaults to 15 seconds.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Returns the smoothed gas price in wei.
+    pub fn suggested_gas_price(&self) -> Result<U256, ClientError> {
+        let mut state = self.state.lock().expect("lock is not poisoned");
+
+        if let Some(sample) = *state {
+            if sample.at.elapsed() < self.ttl {
+                return Ok(sample.average);
+            }
+        }
+
+        let price = self.client.gas_price()?;
+        let average = match *state {
+            Some(sample) => {
+                (sample
+                    .average
+                    .saturating_mul(3.into())
+                    .saturating_add(price))
+                    / 4
+            }
+            None => price,
+        };
+        *state = Some(Sample {
+            at: Instant::now(),
+            average,
+        });
+
+        Ok(average)
+    }
+}
+
+/// Suggests fees for each of `percentiles` from the priority fees paid in the
+/// last `block_count` blocks, `None` if the node has no fee history.
+fn fee_history_suggestions(
+    client: &Client,
+    block_count: u64,
+    percentiles: &[f64],
+) -> Result<Option<Vec<GasSuggestion>>, ClientError> {
+    let history = match client.fee_history(block_count, BlockNumber::Latest, percentiles) {
+        Ok(history) => history,
+        // Pre EIP-1559 node.
+        Err(ClientError::JsonRpc(e)) => {
+            log::debug!("eth_feeHistory failed, using eth_gasPrice: {}", e);
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let rewards = history.reward.unwrap_or_default();
+    let base_fee = match history.base_fee_per_gas.last() {
+        Some(base_fee) if !rewards.is_empty() => *base_fee,
+        _ => return Ok(None),
+    };
+
+    let suggestions = (0..percentiles.len())
+        .map(|i| {
+            let priority_fee = average(rewards.iter().filter_map(|block| block.get(i)));
+            GasSuggestion {
+                // Leaves room for the base fee to double before inclusion.
+                max_fee_per_gas: base_fee
+                    .saturating_mul(2.into())
+                    .saturating_add(priority_fee),
+                max_priority_fee_per_gas: priority_fee,
+            }
+        })
+        .collect();
+    Ok(Some(suggestions))
+}
+
+fn legacy_suggestions(client: &Client) -> Result<GasSuggestions, ClientError> {
+    let gas_price = client.gas_price()?;
+
+    let suggestion = GasSuggestion {
+        max_fee_per_gas: gas_price,
+        max_priority_fee_per_gas: gas_price,
+    };
+    Ok(GasSuggestions {
+        slow: suggestion,
+        standard: suggestion,
+        fast: suggestion,
+    })
+}
+
+fn average<'a, I: Iterator<Item = &'a U256>>(values: I) -> U256 {
+    let (sum, count) = values.fold((U256::zero(), 0u64), |(sum, count), value| {
+        (sum.saturating_add(*value), count + 1)
+    });
+
+    if count == 0 {
+        U256::zero()
+    } else {
+        sum / count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::{self, MockServer};
+
+    fn fee_history() -> serde_json::Value {
+        json!({
+            "oldestBlock": "0xc72640",
+            "baseFeePerGas": ["0x2540be400", "0x2540be400", "0x2540be400", "0x3b9aca00"],
+            "gasUsedRatio": [0.5, 0.9, 0.2],
+            "reward": [
+                ["0x3b9aca00", "0x77359400", "0xb2d05e00"],
+                ["0x1", "0x77359400", "0x12a05f200"],
+                ["0x3b9aca00", "0x3b9aca00", "0x77359400"],
+            ],
+        })
+    }
+
+    #[test]
+    fn tiers_are_ordered() {
+        let server = MockServer::with_result(fee_history());
+        let client = Client::new(server.url());
+
+        let fees = GasOracle::new().estimate(&client).unwrap();
+
+        assert!(fees.slow.max_priority_fee_per_gas <= fees.standard.max_priority_fee_per_gas);
+        assert!(fees.standard.max_priority_fee_per_gas <= fees.fast.max_priority_fee_per_gas);
+        assert!(fees.slow.max_fee_per_gas <= fees.standard.max_fee_per_gas);
+        assert!(fees.standard.max_fee_per_gas <= fees.fast.max_fee_per_gas);
+
+        // Standard tier: average of 2, 2 and 1 gwei, plus twice the 1 gwei
+        // base fee of the next block.
+        let gwei = U256::exp10(9);
+        assert_eq!(fees.standard.max_priority_fee_per_gas, gwei * 5 / 3);
+        assert_eq!(fees.standard.max_fee_per_gas, gwei * 2 + gwei * 5 / 3);
+    }
+
+    #[test]
+    fn requests_configured_percentiles() {
+        let server = MockServer::with_result(fee_history());
+        let client = Client::new(server.url());
+
+        GasOracle::new()
+            .with_block_count(3)
+            .with_percentiles(25.0, 50.0, 75.0)
+            .estimate(&client)
+            .unwrap();
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["method"], "eth_feeHistory");
+        assert_eq!(
+            request["params"],
+            json!(["0x3", "latest", [25.0, 50.0, 75.0]])
+        );
+    }
+
+    #[test]
+    fn falls_back_to_gas_price() {
+        let server = MockServer::start(|request| match request.json()["method"].as_str() {
+            Some("eth_feeHistory") => test_util::error_response(-32601, "method not found"),
+            _ => test_util::response(json!("0x3b9aca00")),
+        });
+        let client = Client::new(server.url());
+
+        let fees = GasOracle::new().estimate(&client).unwrap();
+
+        let gwei = U256::exp10(9);
+        assert_eq!(fees.slow, fees.fast);
+        assert_eq!(fees.standard.max_fee_per_gas, gwei);
+        assert_eq!(fees.standard.max_priority_fee_per_gas, gwei);
+    }
+
+    #[test]
+    fn fixed_fee_does_not_query_the_node() {
+        let server = MockServer::with_result(json!(null));
+        let client = Client::new(server.url());
+
+        let fees = FixedFee::gas_price(U256::exp10(9)).fees(&client).unwrap();
+
+        assert_eq!(fees.max_fee_per_gas, U256::exp10(9));
+        assert_eq!(fees.max_priority_fee_per_gas, U256::exp10(9));
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn oracle_strategy_uses_standard_tier() {
+        let server = MockServer::with_result(fee_history());
+        let client = Client::new(server.url());
+
+        let strategy: &dyn FeeStrategy = &GasOracle::new();
+        let fees = strategy.fees(&client).unwrap();
+
+        assert_eq!(fees, GasOracle::new().estimate(&client).unwrap().standard);
+    }
+
+    #[test]
+    fn percentile_strategy_requests_one_percentile() {
+        let server = MockServer::with_result(json!({
+            "oldestBlock": "0xc72640",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x3b9aca00", "0x3b9aca00"],
+            "gasUsedRatio": [0.5, 0.9, 0.2],
+            "reward": [["0x77359400"], ["0xb2d05e00"], ["0xee6b2800"]],
+        }));
+        let client = Client::new(server.url());
+
+        let fees = Percentile::new(90.0)
+            .with_block_count(3)
+            .fees(&client)
+            .unwrap();
+
+        // Average of 2, 3 and 4 gwei, plus twice the 1 gwei base fee.
+        let gwei = U256::exp10(9);
+        assert_eq!(fees.max_priority_fee_per_gas, gwei * 3);
+        assert_eq!(fees.max_fee_per_gas, gwei * 5);
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["params"], json!(["0x3", "latest", [90.0]]));
+    }
+
+    #[test]
+    fn percentile_strategy_falls_back_to_gas_price() {
+        let server = MockServer::start(|request| match request.json()["method"].as_str() {
+            Some("eth_feeHistory") => test_util::error_response(-32601, "method not found"),
+            _ => test_util::response(json!("0x3b9aca00")),
+        });
+        let client = Client::new(server.url());
+
+        let fees = Percentile::new(50.0).fees(&client).unwrap();
+
+        assert_eq!(fees, FixedFee::gas_price(U256::exp10(9)).0);
+    }
+
+    /// Answers `eth_gasPrice` with 100, 200, 300... wei.
+    fn rising_gas_price() -> MockServer {
+        let calls = std::sync::atomic::AtomicU64::new(0);
+        MockServer::start(move |_| {
+            let n = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            test_util::response(json!(format!("0x{:x}", n * 100)))
+        })
+    }
+
+    #[test]
+    fn tracker_caches_within_ttl() {
+        let server = rising_gas_price();
+        let tracker = GasTracker::new(Client::new(server.url())).with_ttl(Duration::from_secs(60));
+
+        for _ in 0..5 {
+            assert_eq!(tracker.suggested_gas_price().unwrap(), 100.into());
+        }
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn tracker_smooths_new_samples() {
+        let server = rising_gas_price();
+        let tracker = GasTracker::new(Client::new(server.url())).with_ttl(Duration::from_secs(0));
+
+        assert_eq!(tracker.suggested_gas_price().unwrap(), 100.into());
+        // (100 * 3 + 200) / 4
+        assert_eq!(tracker.suggested_gas_price().unwrap(), 125.into());
+        assert_eq!(server.requests().len(), 2);
+    }
+}
@@ -0,0 +1,274 @@
+//! Hierarchical deterministic keys: BIP-39 mnemonics and BIP-32/BIP-44
+//! derivation along the standard Ethereum path.
+//! ref: https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+//! ref: https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki
+
+use hmac::{Hmac, Mac, NewMac};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256, Sha512};
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// The BIP-39 English word list, in order.
+const ENGLISH: &str = include_str!("hd/english.txt");
+
+/// Bits of entropy or checksum encoded by each word.
+const BITS_PER_WORD: usize = 11;
+
+/// Number of PBKDF2 rounds used to stretch a mnemonic into a seed.
+const PBKDF2_ROUNDS: u32 = 2048;
+
+/// Offset used to denote hardened child indices.
+const HARDENED: u32 = 0x8000_0000;
+
+/// Derives the secret key for `account_index` from a BIP-39 mnemonic.
+///
+/// The key is derived along the BIP-44 path `m/44'/60'/0'/0/{index}`, the
+/// same path used by MetaMask and most other wallets. The phrase must be a
+/// valid English mnemonic, see `validate_mnemonic`.
+pub fn secret_key_from_mnemonic(
+    phrase: &str,
+    account_index: u32,
+) -> Result<SecretKey, DeriveError> {
+    validate_mnemonic(phrase)?;
+    let seed = seed_from_mnemonic(phrase, "");
+    let path = [44 | HARDENED, 60 | HARDENED, HARDENED, 0, account_index];
+
+    derive(&seed, &path)
+}
+
+/// Error returned when a secret key cannot be derived from a mnemonic.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum DeriveError {
+    /// The phrase is not a valid mnemonic.
+    #[error("invalid mnemonic")]
+    Mnemonic(#[from] InvalidMnemonic),
+    /// The seed does not give a valid master key.
+    #[error("invalid master key")]
+    InvalidMasterKey,
+    /// The child key at `index` is invalid (vanishingly unlikely), BIP-32
+    /// says to move on to the next index.
+    #[error("invalid child key at index {index}")]
+    InvalidKey { index: u32 },
+}
+
+/// Error returned when a phrase is not a valid BIP-39 mnemonic.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum InvalidMnemonic {
+    /// Mnemonics have 12, 15, 18, 21 or 24 words.
+    #[error("invalid mnemonic length: {0} words")]
+    WordCount(usize),
+    /// The word is not in the BIP-39 English word list.
+    #[error("unknown mnemonic word: {0}")]
+    UnknownWord(String),
+    /// The checksum in the last word does not match the entropy.
+    #[error("invalid mnemonic checksum")]
+    Checksum,
+}
+
+/// Checks that `phrase` is a BIP-39 mnemonic: it has a valid number of
+/// words from the English word list and its checksum matches. The phrase is
+/// NFKD normalized first.
+pub fn validate_mnemonic(phrase: &str) -> Result<(), InvalidMnemonic> {
+    let phrase = phrase.nfkd().collect::<String>();
+    let words = phrase.split_whitespace().collect::<Vec<_>>();
+    if ![12, 15, 18, 21, 24].contains(&words.len()) {
+        return Err(InvalidMnemonic::WordCount(words.len()));
+    }
+
+    let list = ENGLISH.lines().collect::<Vec<_>>();
+    let mut bits = Vec::with_capacity(words.len() * BITS_PER_WORD);
+    for word in &words {
+        let index = list
+            .binary_search(word)
+            .map_err(|_| InvalidMnemonic::UnknownWord((*word).to_owned()))?;
+        bits.extend((0..BITS_PER_WORD).rev().map(|bit| (index >> bit) & 1 == 1));
+    }
+
+    // One checksum bit per 32 bits of entropy.
+    let checksum_len = bits.len() / 33;
+    let (entropy_bits, checksum) = bits.split_at(bits.len() - checksum_len);
+    let entropy = entropy_bits
+        .chunks(8)
+        .map(|byte| {
+            byte.iter()
+                .fold(0u8, |acc, bit| (acc << 1) | u8::from(*bit))
+        })
+        .collect::<Vec<_>>();
+    let hash = Sha256::digest(&entropy);
+    let expected = (0..checksum_len).map(|i| (hash[i / 8] >> (7 - i % 8)) & 1 == 1);
+
+    if !checksum.iter().copied().eq(expected) {
+        return Err(InvalidMnemonic::Checksum);
+    }
+
+    Ok(())
+}
+
+/// Converts a mnemonic (and optional passphrase) into a 64 byte seed. Both
+/// are NFKD normalized, the phrase is not validated.
+pub fn seed_from_mnemonic(phrase: &str, passphrase: &str) -> [u8; 64] {
+    let phrase = phrase.nfkd().collect::<String>();
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let salt = format!("mnemonic{}", passphrase).nfkd().collect::<String>();
+
+    let mut seed = [0u8; 64];
+    pbkdf2::pbkdf2::<HmacSha512>(phrase.as_bytes(), salt.as_bytes(), PBKDF2_ROUNDS, &mut seed);
+    seed
+}
+
+/// Derives the secret key at `path` from `seed`, indices greater than or
+/// equal to `0x8000_0000` are hardened.
+fn derive(seed: &[u8], path: &[u32]) -> Result<SecretKey, DeriveError> {
+    let secp = Secp256k1::signing_only();
+
+    let (key, mut chain_code) = split(hmac_sha512(b"Bitcoin seed", &[seed]));
+    let mut sk = SecretKey::from_slice(&key).map_err(|_| DeriveError::InvalidMasterKey)?;
+
+    for &index in path {
+        let index_bytes = index.to_be_bytes();
+        let output = if index & HARDENED != 0 {
+            hmac_sha512(&chain_code, &[&[0], &sk[..], &index_bytes])
+        } else {
+            let pk = PublicKey::from_secret_key(&secp, &sk).serialize();
+            hmac_sha512(&chain_code, &[&pk, &index_bytes])
+        };
+        let (tweak, next_chain_code) = split(output);
+
+        sk.add_assign(&tweak)
+            .map_err(|_| DeriveError::InvalidKey { index })?;
+        chain_code = next_chain_code;
+    }
+
+    Ok(sk)
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for d in data {
+        mac.update(d);
+    }
+
+    let mut output = [0u8; 64];
+    output.copy_from_slice(&mac.finalize().into_bytes());
+    output
+}
+
+fn split(bytes: [u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&bytes[..32]);
+    right.copy_from_slice(&bytes[32..]);
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{address_from_secret_key, Address};
+
+    const MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    #[test]
+    fn derives_well_known_addresses() {
+        let addresses = [
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+            "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC",
+        ];
+
+        for (index, want) in (0..).zip(addresses.iter()) {
+            let sk = secret_key_from_mnemonic(MNEMONIC, index).unwrap();
+            let got = address_from_secret_key(&sk);
+
+            assert_eq!(got, Address::from_str(want).unwrap());
+        }
+    }
+
+    #[test]
+    fn derives_well_known_secret_key() {
+        let sk = secret_key_from_mnemonic(MNEMONIC, 0).unwrap();
+
+        assert_eq!(
+            hex::encode(&sk[..]),
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        );
+    }
+
+    #[test]
+    fn accepts_bip39_test_vectors() {
+        // From https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+        let vectors = [
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage above",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+            "gravity machine north sort system female filter attitude volume fold club stay \
+             feature office ecology stable narrow fog",
+            "void come effort suffer camp survey warrior heavy shoot primary clutch crush open \
+             amazing screen patrol group space point ten exist slush involve unfold",
+        ];
+
+        for phrase in &vectors {
+            assert_eq!(validate_mnemonic(phrase), Ok(()), "{}", phrase);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_mnemonics() {
+        assert_eq!(
+            validate_mnemonic("test test test"),
+            Err(InvalidMnemonic::WordCount(3))
+        );
+        assert_eq!(
+            validate_mnemonic(&MNEMONIC.replace("junk", "jumk")),
+            Err(InvalidMnemonic::UnknownWord("jumk".to_owned()))
+        );
+        assert_eq!(
+            validate_mnemonic(&MNEMONIC.replace("junk", "test")),
+            Err(InvalidMnemonic::Checksum)
+        );
+        assert_eq!(
+            secret_key_from_mnemonic(&MNEMONIC.replace("junk", "test"), 0),
+            Err(DeriveError::Mnemonic(InvalidMnemonic::Checksum))
+        );
+    }
+
+    #[test]
+    fn seed_matches_bip39_test_vector() {
+        // The first vector from python-mnemonic, with passphrase "TREZOR".
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+        let seed = seed_from_mnemonic(phrase, "TREZOR");
+
+        assert_eq!(
+            hex::encode(&seed[..]),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1\
+             e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+
+    #[test]
+    fn passphrase_is_nfkd_normalized() {
+        // "é" precomposed and as "e" followed by a combining acute accent.
+        assert_eq!(
+            seed_from_mnemonic(MNEMONIC, "caf\u{e9}")[..],
+            seed_from_mnemonic(MNEMONIC, "cafe\u{301}")[..]
+        );
+    }
+
+    #[test]
+    fn mnemonic_whitespace_is_normalized() {
+        let spaced = MNEMONIC.replace(' ', "  \n");
+
+        assert_eq!(
+            secret_key_from_mnemonic(&spaced, 0).unwrap(),
+            secret_key_from_mnemonic(MNEMONIC, 0).unwrap()
+        );
+    }
+}
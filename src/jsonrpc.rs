@@ -1,31 +1,228 @@
 //! JSON RPC client using `ureq` (blocking IO).
-use std::fmt::Debug;
+use std::convert::TryFrom;
+use std::env;
+use std::error::Error as StdError;
+use std::fmt::{self, Debug};
+use std::io;
+use std::num::ParseIntError;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{Context, Result};
+use percent_encoding::percent_decode_str;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 use ureq::{Agent, AgentBuilder};
 pub use url::Url;
 
-#[derive(Clone, Debug)]
+pub use self::stream::ResultStream;
+use self::vcr::{Cassette, Mode};
+use crate::types::{ParseQuantityError, U256};
+
+mod stream;
+mod vcr;
+
+/// Callback invoked with the serialized request and the raw response body.
+pub type Logger = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Credentials sent in the `Authorization` header of every request.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// HTTP Basic authentication.
+    Basic { user: String, pass: String },
+    /// Bearer token authentication.
+    Bearer(String),
+}
+
+impl Auth {
+    /// Returns the value of the `Authorization` header.
+    fn header_value(&self) -> String {
+        match self {
+            Auth::Basic { user, pass } => {
+                format!("Basic {}", base64::encode(format!("{}:{}", user, pass)))
+            }
+            Auth::Bearer(token) => format!("Bearer {}", token),
+        }
+    }
+}
+
+// Manual implementation so credentials do not end up in logs.
+impl Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Auth::Basic { user, .. } => f
+                .debug_struct("Basic")
+                .field("user", user)
+                .field("pass", &"<redacted>")
+                .finish(),
+            Auth::Bearer(_) => f.debug_tuple("Bearer").field(&"<redacted>").finish(),
+        }
+    }
+}
+
+/// A JSON-RPC client.
+///
+/// Cloning a client is cheap: clones share the `ureq::Agent`, and so its
+/// connection pool, as well as the logger and cassette.
+#[derive(Clone)]
 pub struct Client {
     agent: ureq::Agent,
     url: Url,
+    auth: Option<Auth>,
+    headers: Vec<(String, String)>,
+    proxy: Option<ureq::Proxy>,
+    jsonrpc_version: Option<String>,
+    method_prefix: Option<String>,
+    numeric_ids: bool,
+    logger: Option<Logger>,
+    cassette: Option<Arc<Cassette>>,
 }
 
 impl Client {
     /// Construct a new client using `url` as the base URL to connect to.
     pub fn new(url: Url) -> Self {
-        let agent: Agent = AgentBuilder::new()
-            .timeout_read(Duration::from_secs(5))
-            .timeout_write(Duration::from_secs(5))
-            .build();
+        let agent: Agent = agent_builder().build();
+
+        Self {
+            agent,
+            url,
+            auth: None,
+            headers: Vec::new(),
+            proxy: None,
+            jsonrpc_version: None,
+            method_prefix: None,
+            numeric_ids: false,
+            logger: None,
+            cassette: None,
+        }
+    }
+
+    /// Construct a new client that sends requests with `agent`, e.g. one
+    /// shared with the rest of an application to reuse its connection pool
+    /// and TLS configuration. The agent's timeouts are used as is.
+    pub fn with_agent(url: Url, agent: Agent) -> Self {
+        Self {
+            agent,
+            ..Self::new(url)
+        }
+    }
+
+    /// Authenticates every request with `auth`.
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Construct a new client that connects to `url` via the HTTP proxy at
+    /// `proxy`, credentials in the proxy URL are used to authenticate. The
+    /// proxy URL must have a port unless its scheme has a default one.
+    pub fn with_proxy(url: Url, proxy: Url) -> Result<Self, ClientError> {
+        let host = proxy.host_str().ok_or(url::ParseError::EmptyHost)?;
+        let port = proxy
+            .port_or_known_default()
+            .ok_or(url::ParseError::InvalidPort)?;
+        let credentials = if proxy.username().is_empty() && proxy.password().is_none() {
+            String::new()
+        } else {
+            // `ureq` takes the credentials verbatim, a username without a
+            // password is sent with an empty one.
+            let decode = |s: &str| {
+                percent_decode_str(s)
+                    .decode_utf8()
+                    .map(|s| s.into_owned())
+                    .map_err(ClientError::transport)
+            };
+            format!(
+                "{}:{}@",
+                decode(proxy.username())?,
+                decode(proxy.password().unwrap_or_default())?
+            )
+        };
+        let proxy = ureq::Proxy::new(format!(
+            "{}://{}{}:{}",
+            proxy.scheme(),
+            credentials,
+            host,
+            port
+        ))?;
+
+        Ok(Self {
+            agent: agent_builder().proxy(proxy.clone()).build(),
+            proxy: Some(proxy),
+            ..Self::new(url)
+        })
+    }
+
+    /// Construct a new client that uses the proxy set in the `HTTPS_PROXY`
+    /// (for https URLs) or `HTTP_PROXY` environment variable, if any.
+    ///
+    /// No proxy is used if the host of `url` is listed in `NO_PROXY`: a comma
+    /// separated list of host names (each also matching its subdomains),
+    /// optionally with a port, or `*` for every host. IP ranges are not
+    /// supported.
+    pub fn with_proxy_from_env(url: Url) -> Result<Self, ClientError> {
+        match proxy_for(&url, |var| env::var(var).ok()) {
+            Some(proxy) => Self::with_proxy(url, Url::parse(&proxy)?),
+            None => Ok(Self::new(url)),
+        }
+    }
+
+    /// Sends the HTTP header `name: value` with every request, e.g. an API
+    /// key header required by a node provider.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Sends every request with `version` in the `jsonrpc` field instead of
+    /// the version the request was built with, for endpoints that only speak
+    /// e.g. JSON-RPC 1.0.
+    pub fn with_jsonrpc_version(mut self, version: &str) -> Self {
+        self.jsonrpc_version = Some(version.to_owned());
+        self
+    }
+
+    /// Prepends `prefix` to the method name of every request, for endpoints
+    /// that namespace their methods.
+    pub fn with_method_prefix(mut self, prefix: &str) -> Self {
+        self.method_prefix = Some(prefix.to_owned());
+        self
+    }
+
+    /// Sends request ids as JSON numbers instead of strings, for strict
+    /// servers that reject string ids. Ids that are not numeric are sent as
+    /// is.
+    pub fn with_numeric_ids(mut self) -> Self {
+        self.numeric_ids = true;
+        self
+    }
 
-        Self { agent, url }
+    /// Calls `logger` with the serialized request and raw response body of
+    /// every request, before the response is deserialized.
+    pub fn with_logger<F>(mut self, logger: F) -> Self
+    where
+        F: Fn(&str, &str) + Send + Sync + 'static,
+    {
+        self.logger = Some(Arc::new(logger));
+        self
+    }
+
+    /// Records every request and its response to the JSON file at `path`,
+    /// for later use with `replaying`. The file is overwritten.
+    pub fn recording<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.cassette = Some(Arc::new(Cassette::record(path.into())));
+        self
+    }
+
+    /// Serves responses from a file written by `recording` instead of
+    /// sending requests to the node. Requests that were not recorded fail
+    /// with a `Transport` error.
+    pub fn replaying<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        self.cassette = Some(Arc::new(Cassette::replay(path.as_ref())?));
+        Ok(self)
     }
 
-    pub fn send<Req, Res>(&self, request: Request<Req>) -> Result<Res>
+    pub fn send<Req, Res>(&self, request: Request<Req>) -> Result<Res, ClientError>
     where
         Req: Debug + Serialize,
         Res: Debug + DeserializeOwned,
@@ -33,38 +230,404 @@ impl Client {
         self.send_with_path("".into(), request)
     }
 
-    pub fn send_with_path<Req, Res>(&self, path: String, request: Request<Req>) -> Result<Res>
+    /// Like `send` but gives up if the request takes longer than `timeout`,
+    /// e.g. to allow a wide `eth_getLogs` query more time than other calls.
+    ///
+    /// ureq has no per-request timeout so the request is sent on its own
+    /// connection, with the client's proxy (if any) but not the settings of
+    /// an agent passed to `with_agent`.
+    pub fn send_with_timeout<Req, Res>(
+        &self,
+        request: Request<Req>,
+        timeout: Duration,
+    ) -> Result<Res, ClientError>
+    where
+        Req: Debug + Serialize,
+        Res: Debug + DeserializeOwned,
+    {
+        let mut builder = AgentBuilder::new().timeout(timeout);
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+
+        self.send_via(&builder.build(), "".into(), request)
+    }
+
+    pub fn send_with_path<Req, Res>(
+        &self,
+        path: String,
+        request: Request<Req>,
+    ) -> Result<Res, ClientError>
+    where
+        Req: Debug + Serialize,
+        Res: Debug + DeserializeOwned,
+    {
+        self.send_via(&self.agent, path, request)
+    }
+
+    fn send_via<Req, Res>(
+        &self,
+        agent: &Agent,
+        path: String,
+        mut request: Request<Req>,
+    ) -> Result<Res, ClientError>
     where
         Req: Debug + Serialize,
         Res: Debug + DeserializeOwned,
     {
         let url = self.url.clone().join(&path)?;
+        self.apply_overrides(&mut request);
 
-        let response = self
-            .agent
-            .post(&url.to_string())
-            .send_json(ureq::json!(&request))
-            .context("failed to send request")?
-            .into_json::<Response<Res>>()
-            .context("failed to deserialize JSON response as JSON-RPC response")?
-            .payload
-            .into_result()
-            .with_context(|| {
-                format!(
-                    "JSON-RPC request {} failed",
-                    serde_json::to_string(&request).expect("can always serialize to JSON")
+        log::debug!(
+            "sending JSON-RPC request: {} {}",
+            request.method,
+            serde_json::to_string(&request.params).expect("can always serialize to JSON")
+        );
+
+        let request_body = serde_json::to_string(&request).expect("can always serialize to JSON");
+        let replay = self
+            .cassette
+            .as_deref()
+            .filter(|cassette| cassette.mode() == Mode::Replay);
+
+        let response: Response<Res> = if let Some(cassette) = replay {
+            let body = cassette.response(&request_body)?;
+            self.log_response(&request_body, &body);
+            serde_json::from_str(&body)?
+        } else {
+            let response = self
+                .http_request(agent, &url)
+                .send_json(ureq::json!(&request))?;
+
+            if self.logger.is_some()
+                || self.cassette.is_some()
+                || log::log_enabled!(log::Level::Trace)
+            {
+                let body = response.into_string().map_err(ClientError::transport)?;
+                if let Some(cassette) = &self.cassette {
+                    cassette.save(&request_body, &body)?;
+                }
+                self.log_response(&request_body, &body);
+                serde_json::from_str(&body)?
+            } else {
+                serde_json::from_reader(response.into_reader()).map_err(|e| {
+                    if e.is_io() {
+                        ClientError::transport(e)
+                    } else {
+                        ClientError::Decode(e)
+                    }
+                })?
+            }
+        };
+
+        let response = response.payload.into_result().inspect_err(|e| {
+            if let ClientError::JsonRpc(e) = e {
+                log::warn!(
+                    "JSON-RPC request {} failed with code {}: {}",
+                    request.method,
+                    e.code,
+                    e.message
                 )
-            })?;
+            }
+        })?;
 
         Ok(response)
     }
+
+    /// Sends `request`, whose result is an array, and returns an iterator
+    /// that reads and deserializes the elements one at a time instead of
+    /// buffering the whole response.
+    ///
+    /// The response is still read in full when a logger or cassette is in
+    /// use, or trace logging is enabled.
+    pub fn send_streaming<Req, Res>(
+        &self,
+        mut request: Request<Req>,
+    ) -> Result<ResultStream<Res>, ClientError>
+    where
+        Req: Debug + Serialize,
+        Res: DeserializeOwned,
+    {
+        let url = self.url.clone().join("")?;
+        self.apply_overrides(&mut request);
+
+        log::debug!(
+            "sending streaming JSON-RPC request: {} {}",
+            request.method,
+            serde_json::to_string(&request.params).expect("can always serialize to JSON")
+        );
+
+        let request_body = serde_json::to_string(&request).expect("can always serialize to JSON");
+        let reader: Box<dyn io::Read + Send> = match self.cassette.as_deref() {
+            Some(cassette) if cassette.mode() == Mode::Replay => {
+                let body = cassette.response(&request_body)?;
+                self.log_response(&request_body, &body);
+                Box::new(io::Cursor::new(body.into_bytes()))
+            }
+            cassette => {
+                let response = self
+                    .http_request(&self.agent, &url)
+                    .send_json(ureq::json!(&request))?;
+
+                if cassette.is_some()
+                    || self.logger.is_some()
+                    || log::log_enabled!(log::Level::Trace)
+                {
+                    let body = response.into_string().map_err(ClientError::transport)?;
+                    if let Some(cassette) = cassette {
+                        cassette.save(&request_body, &body)?;
+                    }
+                    self.log_response(&request_body, &body);
+                    Box::new(io::Cursor::new(body.into_bytes()))
+                } else {
+                    Box::new(response.into_reader())
+                }
+            }
+        };
+
+        Ok(ResultStream::new(reader))
+    }
+
+    /// Sends `requests` as a single JSON-RPC batch. Return the result of each
+    /// request in the same order as `requests`, regardless of the order the
+    /// node responds in.
+    ///
+    /// The ids of `requests` are replaced with their position in the batch.
+    pub fn send_batch<Req, Res>(
+        &self,
+        requests: Vec<Request<Req>>,
+    ) -> Result<Vec<Result<Res, ClientError>>, ClientError>
+    where
+        Req: Debug + Serialize,
+        Res: Debug + DeserializeOwned,
+    {
+        let url = self.url.clone().join("")?;
+        let requests: Vec<_> = requests
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut request)| {
+                request.id = Id::Str(i.to_string());
+                self.apply_overrides(&mut request);
+                request
+            })
+            .collect();
+
+        log::debug!("sending JSON-RPC batch of {} requests", requests.len());
+
+        let request_body = serde_json::to_string(&requests).expect("can always serialize to JSON");
+        let body = match self.cassette.as_deref() {
+            Some(cassette) if cassette.mode() == Mode::Replay => {
+                cassette.response(&request_body)?
+            }
+            cassette => {
+                let body = self
+                    .http_request(&self.agent, &url)
+                    .send_json(ureq::json!(&requests))?
+                    .into_string()
+                    .map_err(ClientError::transport)?;
+                if let Some(cassette) = cassette {
+                    cassette.save(&request_body, &body)?;
+                }
+                body
+            }
+        };
+        self.log_response(&request_body, &body);
+
+        let responses: Vec<Response<Res>> = serde_json::from_str(&body)?;
+        let mut results: Vec<Option<Result<Res, ClientError>>> =
+            requests.iter().map(|_| None).collect();
+        for response in responses {
+            let index = response.id.as_ref().and_then(Id::as_index);
+            if let Some(result) = index.and_then(|i| results.get_mut(i)) {
+                *result = Some(response.payload.into_result());
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| {
+                result.ok_or_else(|| {
+                    ClientError::parse(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "JSON-RPC batch response is missing a request",
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Applies the configured JSON-RPC version, method prefix and id type, if
+    /// any.
+    fn apply_overrides<Req>(&self, request: &mut Request<Req>) {
+        if self.numeric_ids {
+            if let Id::Str(id) = &request.id {
+                if let Ok(id) = id.parse() {
+                    request.id = Id::Num(id);
+                }
+            }
+        }
+        if let Some(version) = &self.jsonrpc_version {
+            request.jsonrpc = version.clone();
+        }
+        if let Some(prefix) = &self.method_prefix {
+            request.method = format!("{}{}", prefix, request.method);
+        }
+    }
+
+    /// Returns a POST request to `url` with the configured auth and headers.
+    fn http_request(&self, agent: &Agent, url: &Url) -> ureq::Request {
+        let mut http_request = agent.post(url.as_str());
+        if let Some(auth) = &self.auth {
+            http_request = http_request.set("Authorization", &auth.header_value());
+        }
+        for (name, value) in &self.headers {
+            http_request = http_request.set(name, value);
+        }
+        http_request
+    }
+
+    fn log_response(&self, request: &str, response: &str) {
+        log::trace!("received JSON-RPC response: {}", response);
+
+        if let Some(logger) = &self.logger {
+            logger(request, response);
+        }
+    }
+}
+
+fn agent_builder() -> AgentBuilder {
+    AgentBuilder::new()
+        .timeout_read(Duration::from_secs(5))
+        .timeout_write(Duration::from_secs(5))
+}
+
+/// Returns the proxy to use for `url` from the `HTTPS_PROXY` (for https
+/// URLs) or `HTTP_PROXY` variable, as returned by `lookup`. Lowercase names
+/// are also accepted, empty values are ignored.
+fn proxy_for<F>(url: &Url, lookup: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let no_proxy = ["NO_PROXY", "no_proxy"]
+        .iter()
+        .filter_map(|var| lookup(var))
+        .find(|no_proxy| !no_proxy.is_empty());
+    if let Some(no_proxy) = no_proxy {
+        if no_proxy_matches(url, &no_proxy) {
+            return None;
+        }
+    }
+
+    let vars: &[&str] = if url.scheme() == "https" {
+        &["HTTPS_PROXY", "https_proxy"]
+    } else {
+        &["HTTP_PROXY", "http_proxy"]
+    };
+
+    vars.iter()
+        .filter_map(|var| lookup(var))
+        .find(|proxy| !proxy.is_empty())
+}
+
+/// Returns true if the host of `url` is listed in `no_proxy`, see
+/// `Client::with_proxy_from_env`.
+fn no_proxy_matches(url: &Url, no_proxy: &str) -> bool {
+    let host = match url.host_str() {
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase(),
+        None => return false,
+    };
+    let port = url.port_or_known_default();
+
+    no_proxy
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+
+            let (name, entry_port) = split_no_proxy_port(entry);
+            if entry_port.is_some() && entry_port != port {
+                return false;
+            }
+            let name = name
+                .trim_start_matches("*.")
+                .trim_start_matches('.')
+                .to_ascii_lowercase();
+            host == name || host.ends_with(&format!(".{}", name))
+        })
+}
+
+/// Splits a `NO_PROXY` entry into its host and port, if it has one. IPv6
+/// addresses need brackets to be given a port, e.g. `[::1]:8545`.
+fn split_no_proxy_port(entry: &str) -> (&str, Option<u16>) {
+    if let Some(rest) = entry.strip_prefix('[') {
+        return match rest.split_once(']') {
+            Some((host, port)) => (host, port.strip_prefix(':').and_then(|p| p.parse().ok())),
+            None => (entry, None),
+        };
+    }
+
+    match entry.split_once(':') {
+        Some((host, port)) if !port.contains(':') => (host, port.parse().ok()),
+        _ => (entry, None),
+    }
+}
+
+impl Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("agent", &self.agent)
+            .field("url", &self.url)
+            .field("auth", &self.auth)
+            // Only the names, values may contain API keys.
+            .field(
+                "headers",
+                &self
+                    .headers
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            // The proxy URL may contain credentials.
+            .field("proxy", &self.proxy.is_some())
+            .field("jsonrpc_version", &self.jsonrpc_version)
+            .field("method_prefix", &self.method_prefix)
+            .field("numeric_ids", &self.numeric_ids)
+            .field("logger", &self.logger.is_some())
+            .field("cassette", &self.cassette)
+            .finish()
+    }
 }
 
 pub const JSONRPC_VERSION_2: &str = "2.0";
 
+/// The id of a JSON-RPC request, echoed back in its response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Id {
+    Num(u64),
+    Str(String),
+}
+
+impl Id {
+    /// Returns the id as a position in a batch, nodes echo the id back as
+    /// sent but some turn numeric strings into numbers.
+    fn as_index(&self) -> Option<usize> {
+        match self {
+            Id::Num(id) => usize::try_from(*id).ok(),
+            Id::Str(id) => id.parse().ok(),
+        }
+    }
+}
+
 #[derive(serde::Serialize, Debug, Clone)]
 pub struct Request<T> {
-    id: String,
+    id: Id,
     jsonrpc: String,
     method: String,
     params: T,
@@ -74,7 +637,7 @@ impl<T> Request<T> {
     /// Construct a new request.
     pub fn new(method: &str, params: T, jsonrpc: String) -> Self {
         Self {
-            id: "1".to_owned(),
+            id: Id::Str("1".to_owned()),
             jsonrpc,
             method: method.to_owned(),
             params,
@@ -85,10 +648,19 @@ impl<T> Request<T> {
     pub fn v2(method: &str, params: T) -> Self {
         Self::new(method, params, JSONRPC_VERSION_2.into())
     }
+
+    /// Sets the id of the request, the default is `"1"`.
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = id;
+        self
+    }
 }
 
 #[derive(serde::Deserialize, Debug, PartialEq)]
 pub struct Response<R> {
+    /// The id of the request, `None` if the node could not read it.
+    #[serde(default)]
+    pub id: Option<Id>,
     #[serde(flatten)]
     pub payload: ResponsePayload<R>,
 }
@@ -96,31 +668,809 @@ pub struct Response<R> {
 #[derive(Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ResponsePayload<R> {
-    Result(R),
+    /// The result, `None` if it is JSON `null`.
+    Result(Option<R>),
     Error(JsonRpcError),
 }
 
-impl<R> ResponsePayload<R> {
-    fn into_result(self) -> Result<R, JsonRpcError> {
+impl<R: DeserializeOwned> ResponsePayload<R> {
+    /// Returns the result, a `null` result is only accepted if `R` can be
+    /// deserialized from `null` (e.g. an `Option`).
+    fn into_result(self) -> Result<R, ClientError> {
         match self {
-            ResponsePayload::Result(result) => Ok(result),
-            ResponsePayload::Error(e) => Err(e),
+            ResponsePayload::Result(Some(result)) => Ok(result),
+            ResponsePayload::Result(None) => {
+                R::deserialize(serde_json::Value::Null).map_err(|_| ClientError::NullResult)
+            }
+            ResponsePayload::Error(e) => Err(ClientError::JsonRpc(e)),
         }
     }
 }
 
+/// Errors returned by the JSON-RPC clients.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The request could not be sent or the response could not be read.
+    #[error("failed to send JSON-RPC request")]
+    Transport(#[source] Box<dyn StdError + Send + Sync>),
+    /// The node returned a JSON-RPC error.
+    #[error(transparent)]
+    JsonRpc(#[from] JsonRpcError),
+    /// JSON serialization or deserialization failed.
+    #[error("failed to deserialize JSON-RPC response")]
+    Decode(#[from] serde_json::Error),
+    /// The result could not be parsed into the expected type.
+    #[error("failed to parse JSON-RPC result")]
+    Parse(#[source] Box<dyn StdError + Send + Sync>),
+    /// The node returned a `null` result where a value was expected, e.g.
+    /// for an unknown block or transaction.
+    #[error("JSON-RPC result is null")]
+    NullResult,
+    /// The current gas price is above the most the caller is willing to pay.
+    #[error("gas price {price} exceeds cap of {max}")]
+    GasTooHigh { price: U256, max: U256 },
+    /// The request was rejected before being sent, e.g. malformed params.
+    #[error("invalid JSON-RPC request: {0}")]
+    InvalidRequest(String),
+}
+
+impl ClientError {
+    fn transport<E>(e: E) -> Self
+    where
+        E: StdError + Send + Sync + 'static,
+    {
+        ClientError::Transport(Box::new(e))
+    }
+
+    /// Construct a `Parse` error.
+    pub fn parse<E>(e: E) -> Self
+    where
+        E: StdError + Send + Sync + 'static,
+    {
+        ClientError::Parse(Box::new(e))
+    }
+}
+
+impl From<ureq::Error> for ClientError {
+    fn from(e: ureq::Error) -> Self {
+        ClientError::transport(e)
+    }
+}
+
+impl From<url::ParseError> for ClientError {
+    fn from(e: url::ParseError) -> Self {
+        ClientError::transport(e)
+    }
+}
+
+impl From<ParseIntError> for ClientError {
+    fn from(e: ParseIntError) -> Self {
+        ClientError::parse(e)
+    }
+}
+
+impl From<ParseQuantityError> for ClientError {
+    fn from(e: ParseQuantityError) -> Self {
+        ClientError::parse(e)
+    }
+}
+
 #[derive(Debug, Deserialize, Error, PartialEq)]
 #[error("JSON-RPC request failed with code {code}: {message}")]
 pub struct JsonRpcError {
-    code: i64,
-    message: String,
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+/// Function selector for `Error(string)`, used by Solidity to encode revert
+/// reasons.
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+impl JsonRpcError {
+    /// Returns true if the node refused the request because the result would
+    /// be too large, e.g. an `eth_getLogs` query matching too many logs.
+    ///
+    /// Providers do not agree on an error code so we also check the message.
+    pub fn is_result_limit(&self) -> bool {
+        const LIMIT_MESSAGES: [&str; 4] = [
+            "more than",
+            "too many",
+            "response size exceeded",
+            "limit exceeded",
+        ];
+
+        let message = self.message.to_lowercase();
+        self.code == -32005 || LIMIT_MESSAGES.iter().any(|m| message.contains(m))
+    }
+
+    /// Returns the reason a call reverted.
+    ///
+    /// The `data` field is first decoded as an ABI encoded `Error(string)`,
+    /// if that fails and `data` is UTF-8 text without control characters it
+    /// is returned as is, otherwise we fall back to the error message (e.g.
+    /// for a custom error, whose data is a selector and ABI encoded words).
+    pub fn revert_reason(&self) -> String {
+        let data = match self.data.as_ref().and_then(|data| data.as_str()) {
+            Some(data) => data,
+            None => return self.message.clone(),
+        };
+        let bytes = match hex::decode(data.strip_prefix("0x").unwrap_or(data)) {
+            Ok(bytes) => bytes,
+            Err(_) => return self.message.clone(),
+        };
+
+        if let Some(encoded) = bytes.strip_prefix(&ERROR_STRING_SELECTOR) {
+            if let Ok(tokens) = ethabi::decode(&[ethabi::ParamType::String], encoded) {
+                if let Some(ethabi::Token::String(reason)) = tokens.into_iter().next() {
+                    return reason;
+                }
+            }
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(reason) if !reason.is_empty() && !reason.chars().any(char::is_control) => reason,
+            _ => self.message.clone(),
+        }
+    }
 }
 
-pub fn serialize<T>(t: T) -> Result<serde_json::Value>
+pub fn serialize<T>(t: T) -> Result<serde_json::Value, ClientError>
 where
     T: Serialize,
 {
-    let value = serde_json::to_value(t).context("failed to serialize parameter")?;
+    let value = serde_json::to_value(t)?;
 
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::test_util::{self, MockProxy, MockServer};
+
+    struct CapturingLogger;
+
+    static CAPTURED: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            CAPTURED
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger;
+
+    #[test]
+    fn send_emits_log_records() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let server = MockServer::start(|_| test_util::error_response(-32000, "nonce too low"));
+        let client = Client::new(server.url());
+
+        let res: Result<String, _> = client.send(Request::v2("eth_logTestMethod", vec!["0x1"]));
+        assert!(res.is_err());
+
+        let captured = CAPTURED.lock().unwrap();
+        assert!(captured
+            .iter()
+            .any(|(level, msg)| *level == log::Level::Debug
+                && msg.contains("eth_logTestMethod")
+                && msg.contains("0x1")));
+        assert!(captured
+            .iter()
+            .any(|(level, msg)| *level == log::Level::Trace && msg.contains("nonce too low")));
+        assert!(captured
+            .iter()
+            .any(|(level, msg)| *level == log::Level::Warn
+                && msg.contains("eth_logTestMethod")
+                && msg.contains("-32000")));
+    }
+
+    #[test]
+    fn logger_sees_request_and_response() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let logged = Arc::new(Mutex::new(Vec::new()));
+
+        let captured = Arc::clone(&logged);
+        let client = Client::new(server.url()).with_logger(move |req, res| {
+            captured
+                .lock()
+                .unwrap()
+                .push((req.to_owned(), res.to_owned()));
+        });
+
+        let block_number: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+        assert_eq!(block_number, "0x10");
+
+        let logged = logged.lock().unwrap();
+        assert_eq!(logged.len(), 1);
+
+        let (req, res) = &logged[0];
+        let req: serde_json::Value = serde_json::from_str(req).unwrap();
+        assert_eq!(req["method"], "eth_blockNumber");
+        assert_eq!(res, &test_util::response(serde_json::json!("0x10")));
+    }
+
+    #[test]
+    fn basic_auth_header_is_sent() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let auth = Auth::Basic {
+            user: "alice".to_owned(),
+            pass: "secret".to_owned(),
+        };
+        let client = Client::new(server.url()).with_auth(auth);
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        let request = &server.requests()[0];
+        // base64("alice:secret")
+        assert_eq!(
+            request.header("Authorization"),
+            Some("Basic YWxpY2U6c2VjcmV0")
+        );
+    }
+
+    #[test]
+    fn bearer_auth_header_is_sent() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let client = Client::new(server.url()).with_auth(Auth::Bearer("token".to_owned()));
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("Authorization"), Some("Bearer token"));
+    }
+
+    #[test]
+    fn custom_headers_are_sent() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let client = Client::new(server.url())
+            .with_auth(Auth::Bearer("token".to_owned()))
+            .with_header("x-api-key", "abc123")
+            .with_header("User-Agent", "my-app/1.0");
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("x-api-key"), Some("abc123"));
+        assert_eq!(request.header("user-agent"), Some("my-app/1.0"));
+        assert_eq!(request.header("authorization"), Some("Bearer token"));
+    }
+
+    #[test]
+    fn can_send_with_injected_agent() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let agent = AgentBuilder::new().user_agent("shared-agent/1.0").build();
+        let client = Client::with_agent(server.url(), agent);
+
+        let res: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+        assert_eq!(res, "0x10");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("user-agent"), Some("shared-agent/1.0"));
+    }
+
+    #[test]
+    fn slow_response_exceeds_per_call_timeout() {
+        let server = MockServer::start(|_| {
+            std::thread::sleep(Duration::from_millis(500));
+            test_util::response(serde_json::json!("0x10"))
+        });
+        let client = Client::new(server.url());
+
+        let res: Result<String, _> = client.send_with_timeout(
+            Request::v2("eth_blockNumber", Vec::<()>::new()),
+            Duration::from_millis(50),
+        );
+        assert!(matches!(res, Err(ClientError::Transport(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn per_call_timeout_allows_slow_response() {
+        let server = MockServer::start(|_| {
+            std::thread::sleep(Duration::from_millis(50));
+            test_util::response(serde_json::json!("0x10"))
+        });
+        let client = Client::new(server.url());
+
+        let res: String = client
+            .send_with_timeout(
+                Request::v2("eth_blockNumber", Vec::<()>::new()),
+                Duration::from_secs(10),
+            )
+            .unwrap();
+        assert_eq!(res, "0x10");
+    }
+
+    #[test]
+    fn configured_version_and_method_prefix_are_sent() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let client = Client::new(server.url())
+            .with_jsonrpc_version("1.0")
+            .with_method_prefix("legacy_");
+
+        let _: String = client
+            .send(Request::v2("blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["jsonrpc"], "1.0");
+        assert_eq!(request["method"], "legacy_blockNumber");
+    }
+
+    #[test]
+    fn requests_default_to_version_2() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let client = Client::new(server.url());
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["jsonrpc"], JSONRPC_VERSION_2);
+        assert_eq!(request["method"], "eth_blockNumber");
+    }
+
+    #[test]
+    fn request_id_can_be_numeric() {
+        let request = Request::v2("eth_blockNumber", Vec::<()>::new()).with_id(Id::Num(7));
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["id"], serde_json::json!(7));
+
+        let request = Request::v2("eth_blockNumber", Vec::<()>::new());
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["id"], serde_json::json!("1"));
+    }
+
+    #[test]
+    fn numeric_ids_are_sent() {
+        let server = MockServer::with_result(serde_json::json!("0x10"));
+        let client = Client::new(server.url()).with_numeric_ids();
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        let request = server.requests()[0].json();
+        assert_eq!(request["id"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn response_accepts_string_and_numeric_ids() {
+        let response: Response<String> =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":"1","result":"0x10"}"#).unwrap();
+        assert_eq!(response.id, Some(Id::Str("1".to_owned())));
+        assert_eq!(
+            response.payload,
+            ResponsePayload::Result(Some("0x10".to_owned()))
+        );
+
+        let response: Response<String> =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":"0x10"}"#).unwrap();
+        assert_eq!(response.id, Some(Id::Num(1)));
+
+        let response: Response<String> = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"parse error"}}"#,
+        )
+        .unwrap();
+        assert_eq!(response.id, None);
+    }
+
+    #[test]
+    fn null_result_is_none_for_option() {
+        let server = MockServer::with_result(serde_json::json!(null));
+        let client = Client::new(server.url());
+
+        let receipt: Option<crate::types::TransactionReceipt> = client
+            .send(Request::v2("eth_getTransactionReceipt", vec!["0x00"]))
+            .unwrap();
+        assert_eq!(receipt, None);
+    }
+
+    #[test]
+    fn null_result_where_value_expected_fails() {
+        let server = MockServer::with_result(serde_json::json!(null));
+        let client = Client::new(server.url());
+
+        let res: Result<String, _> = client.send(Request::v2("eth_blockNumber", Vec::<()>::new()));
+        assert!(matches!(res, Err(ClientError::NullResult)), "{:?}", res);
+
+        let res: Result<crate::types::TransactionReceipt, _> =
+            client.send(Request::v2("eth_getTransactionReceipt", vec!["0x00"]));
+        assert!(matches!(res, Err(ClientError::NullResult)), "{:?}", res);
+    }
+
+    #[test]
+    fn cloned_clients_share_connections() {
+        let server =
+            MockServer::start_keep_alive(|_| test_util::response(serde_json::json!("0x10")));
+        let client = Client::new(server.url());
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..5 {
+                        let res: String = client
+                            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+                            .unwrap();
+                        assert_eq!(res, "0x10");
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(server.requests().len(), 20);
+
+        // A fresh clone picks up a connection left in the shared pool.
+        let connections = server.connections();
+        let _: String = client
+            .clone()
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+        assert_eq!(server.connections(), connections);
+    }
+
+    #[test]
+    fn auth_debug_does_not_leak_credentials() {
+        let auth = Auth::Basic {
+            user: "alice".to_owned(),
+            pass: "secret".to_owned(),
+        };
+        assert!(!format!("{:?}", auth).contains("secret"));
+
+        let auth = Auth::Bearer("token".to_owned());
+        assert!(!format!("{:?}", auth).contains("token"));
+    }
+
+    #[test]
+    fn requests_are_routed_through_proxy() {
+        let proxy = MockProxy::with_result(serde_json::json!("0x10"));
+        let url = Url::parse("http://node.invalid:8545/").unwrap();
+        let client = Client::with_proxy(url, proxy.url()).unwrap();
+
+        let block_number: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        assert_eq!(block_number, "0x10");
+        assert_eq!(proxy.targets(), vec!["node.invalid:8545"]);
+    }
+
+    #[test]
+    fn auth_is_sent_through_proxy() {
+        let proxy = MockProxy::with_result(serde_json::json!("0x10"));
+        let url = Url::parse("http://node.invalid:8545/").unwrap();
+        let client = Client::with_proxy(url, proxy.url())
+            .unwrap()
+            .with_auth(Auth::Bearer("token".to_owned()));
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        let requests = proxy.requests();
+        assert_eq!(requests[0].header("authorization"), Some("Bearer token"));
+    }
+
+    #[test]
+    fn proxy_credentials_are_decoded() {
+        let proxy = MockProxy::with_result(serde_json::json!("0x10"));
+        let mut proxy_url = proxy.url();
+        proxy_url.set_username("al%40ice").unwrap();
+        proxy_url.set_password(Some("p%3Ass")).unwrap();
+        let url = Url::parse("http://node.invalid:8545/").unwrap();
+        let client = Client::with_proxy(url, proxy_url).unwrap();
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        // base64("al@ice:p:ss")
+        let connects = proxy.connects();
+        assert_eq!(
+            connects[0].header("proxy-authorization"),
+            Some("basic YWxAaWNlOnA6c3M=")
+        );
+    }
+
+    #[test]
+    fn proxy_username_without_password_is_kept() {
+        let proxy = MockProxy::with_result(serde_json::json!("0x10"));
+        let mut proxy_url = proxy.url();
+        proxy_url.set_username("alice").unwrap();
+        let url = Url::parse("http://node.invalid:8545/").unwrap();
+        let client = Client::with_proxy(url, proxy_url).unwrap();
+
+        let _: String = client
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+
+        // base64("alice:")
+        let connects = proxy.connects();
+        assert_eq!(
+            connects[0].header("proxy-authorization"),
+            Some("basic YWxpY2U6")
+        );
+    }
+
+    #[test]
+    fn proxy_without_port_is_an_error() {
+        let url = Url::parse("http://node.invalid:8545/").unwrap();
+        let proxy = Url::parse("socks5://proxy.invalid").unwrap();
+
+        assert!(Client::with_proxy(url, proxy).is_err());
+    }
+
+    #[test]
+    fn proxy_is_chosen_by_url_scheme() {
+        let vars = |var: &str| match var {
+            "HTTPS_PROXY" => Some(String::new()),
+            "https_proxy" => Some("http://secure.invalid:3128".to_owned()),
+            "HTTP_PROXY" => Some("http://plain.invalid:3128".to_owned()),
+            _ => None,
+        };
+        let https = Url::parse("https://node.invalid/").unwrap();
+        let http = Url::parse("http://node.invalid/").unwrap();
+
+        assert_eq!(
+            proxy_for(&https, vars).as_deref(),
+            Some("http://secure.invalid:3128")
+        );
+        assert_eq!(
+            proxy_for(&http, vars).as_deref(),
+            Some("http://plain.invalid:3128")
+        );
+        assert_eq!(proxy_for(&http, |_| None), None);
+    }
+
+    #[test]
+    fn no_proxy_hosts_are_not_proxied() {
+        let vars = |no_proxy: &'static str| {
+            move |var: &str| match var {
+                "HTTP_PROXY" => Some("http://proxy.invalid:3128".to_owned()),
+                "no_proxy" => Some(no_proxy.to_owned()),
+                _ => None,
+            }
+        };
+        let proxied =
+            |url: &str, no_proxy| proxy_for(&Url::parse(url).unwrap(), vars(no_proxy)).is_some();
+
+        assert!(!proxied("http://localhost:8545/", "localhost,127.0.0.1"));
+        assert!(!proxied("http://127.0.0.1:8545/", "localhost, 127.0.0.1"));
+        assert!(!proxied("http://[::1]:8545/", "::1"));
+        assert!(!proxied("http://node.example.com/", ".example.com"));
+        assert!(!proxied("http://node.example.com/", "EXAMPLE.com"));
+        assert!(!proxied("http://node.invalid/", "*"));
+        assert!(!proxied("http://node.invalid:8545/", "node.invalid:8545"));
+
+        assert!(proxied("http://node.invalid:80/", "node.invalid:8545"));
+        assert!(proxied("http://notexample.com/", "example.com"));
+        assert!(proxied("http://node.invalid/", "localhost"));
+        assert!(proxied("http://node.invalid/", ""));
+    }
+
+    #[test]
+    fn batch_results_follow_request_order() {
+        // Respond out of order, with one error.
+        let server = MockServer::start(|request| {
+            let requests = request.json();
+            let responses: Vec<_> = requests
+                .as_array()
+                .unwrap()
+                .iter()
+                .rev()
+                .map(|request| match request["params"][0].as_str() {
+                    Some("bad") => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "error": { "code": -32602, "message": "invalid argument" },
+                    }),
+                    param => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "result": param,
+                    }),
+                })
+                .collect();
+            serde_json::to_string(&responses).unwrap()
+        });
+        let client = Client::new(server.url()).with_method_prefix("test_");
+
+        let results: Vec<Result<String, _>> = client
+            .send_batch(vec![
+                Request::v2("echo", vec!["a"]),
+                Request::v2("echo", vec!["bad"]),
+                Request::v2("echo", vec!["c"]),
+            ])
+            .unwrap();
+
+        assert_eq!(results[0].as_ref().unwrap(), "a");
+        assert!(
+            matches!(&results[1], Err(ClientError::JsonRpc(e)) if e.code == -32602),
+            "{:?}",
+            results[1]
+        );
+        assert_eq!(results[2].as_ref().unwrap(), "c");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let batch = requests[0].json();
+        assert_eq!(batch[2]["id"], "2");
+        assert_eq!(batch[2]["method"], "test_echo");
+    }
+
+    #[test]
+    fn batch_with_missing_response_fails() {
+        let server =
+            MockServer::start(|_| r#"[{"jsonrpc":"2.0","id":"0","result":"0x1"}]"#.to_owned());
+        let client = Client::new(server.url());
+
+        let res: Result<Vec<Result<String, _>>, _> = client.send_batch(vec![
+            Request::v2("eth_blockNumber", Vec::<()>::new()),
+            Request::v2("eth_blockNumber", Vec::<()>::new()),
+        ]);
+        assert!(matches!(res, Err(ClientError::Parse(_))), "{:?}", res);
+    }
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ethereum-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn can_record_and_replay() {
+        let path = cassette_path("record-and-replay");
+        let server = MockServer::start(|request| {
+            let result = match request.json()["method"].as_str() {
+                Some("eth_blockNumber") => "0x10",
+                _ => "0x2a",
+            };
+            test_util::response(serde_json::json!(result))
+        });
+
+        let recorder = Client::new(server.url()).recording(&path);
+        let block_number: String = recorder
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+        let balance: String = recorder
+            .send(Request::v2("eth_getBalance", vec!["0x00", "latest"]))
+            .unwrap();
+        assert_eq!(server.requests().len(), 2);
+
+        // Nothing listens on the discard port, replay must not touch the network.
+        let url = Url::parse("http://127.0.0.1:9/").unwrap();
+        let replayer = Client::new(url).replaying(&path).unwrap();
+        let replayed_block_number: String = replayer
+            .send(Request::v2("eth_blockNumber", Vec::<()>::new()))
+            .unwrap();
+        let replayed_balance: String = replayer
+            .send(Request::v2("eth_getBalance", vec!["0x00", "latest"]))
+            .unwrap();
+
+        assert_eq!(replayed_block_number, block_number);
+        assert_eq!(replayed_balance, balance);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn replaying_unrecorded_request_fails() {
+        let path = cassette_path("unrecorded");
+        std::fs::write(&path, "{}").unwrap();
+
+        let url = Url::parse("http://127.0.0.1:9/").unwrap();
+        let client = Client::new(url).replaying(&path).unwrap();
+        let res: Result<String, _> = client.send(Request::v2("eth_blockNumber", Vec::<()>::new()));
+        assert!(matches!(res, Err(ClientError::Transport(_))), "{:?}", res);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn replaying_missing_cassette_fails() {
+        let url = Url::parse("http://127.0.0.1:9/").unwrap();
+        assert!(Client::new(url)
+            .replaying(cassette_path("does-not-exist"))
+            .is_err());
+    }
+
+    fn error_with_data(data: Option<String>) -> JsonRpcError {
+        JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_owned(),
+            data: data.map(serde_json::Value::String),
+        }
+    }
+
+    #[test]
+    fn revert_reason_decodes_abi_encoded_error() {
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[ethabi::Token::String(
+            "Not enough Ether provided.".to_owned(),
+        )]));
+        let err = error_with_data(Some(format!("0x{}", hex::encode(data))));
+
+        assert_eq!(err.revert_reason(), "Not enough Ether provided.");
+    }
+
+    #[test]
+    fn revert_reason_falls_back_to_plain_text_data() {
+        let data = format!("0x{}", hex::encode("insufficient balance"));
+        let err = error_with_data(Some(data));
+
+        assert_eq!(err.revert_reason(), "insufficient balance");
+    }
+
+    #[test]
+    fn revert_reason_falls_back_to_message() {
+        let err = error_with_data(None);
+        assert_eq!(err.revert_reason(), "execution reverted");
+
+        let err = error_with_data(Some("0x".to_owned()));
+        assert_eq!(err.revert_reason(), "execution reverted");
+
+        // Not valid UTF-8.
+        let err = error_with_data(Some("0xfffe".to_owned()));
+        assert_eq!(err.revert_reason(), "execution reverted");
+    }
+
+    #[test]
+    fn revert_reason_of_custom_error_is_message() {
+        // `SoldOut(uint256)`, every byte is ASCII so the data is valid UTF-8.
+        let mut data = crate::function_selector("SoldOut(uint256)").to_vec();
+        data.extend(ethabi::encode(&[ethabi::Token::Uint(100.into())]));
+        assert!(String::from_utf8(data.clone()).is_ok());
+
+        let err = error_with_data(Some(format!("0x{}", hex::encode(data))));
+        assert_eq!(err.revert_reason(), "execution reverted");
+    }
+
+    #[test]
+    fn detects_result_limit_errors() {
+        let error = |code, message: &str| JsonRpcError {
+            code,
+            message: message.to_owned(),
+            data: None,
+        };
+
+        assert!(error(-32005, "query returned more than 10000 results").is_result_limit());
+        assert!(error(-32602, "Log response size exceeded.").is_result_limit());
+        assert!(error(-32000, "too many results").is_result_limit());
+        assert!(!error(-32000, "header not found").is_result_limit());
+    }
+
+    #[test]
+    fn can_deserialize_error_with_data() {
+        let json = r#"{"code":3,"message":"execution reverted","data":"0x1234"}"#;
+        let err: JsonRpcError = serde_json::from_str(json).unwrap();
+
+        assert_eq!(err, error_with_data(Some("0x1234".to_owned())));
+    }
+}
@@ -0,0 +1,262 @@
+//! Reads the elements of an array JSON-RPC result one at a time, so a large
+//! response (e.g. a wide `eth_getLogs` query) is never held in memory whole.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+
+use super::{ClientError, JsonRpcError};
+
+/// Iterator over the elements of an array JSON-RPC result, each element is
+/// deserialized as it is read from the response.
+///
+/// The iterator ends after the first error.
+pub struct ResultStream<T> {
+    reader: BufReader<Box<dyn Read + Send>>,
+    state: State,
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Before the `result` array.
+    Envelope,
+    /// Before the first element.
+    First,
+    /// After an element.
+    Rest,
+    Done,
+}
+
+impl<T: DeserializeOwned> ResultStream<T> {
+    pub(super) fn new(reader: Box<dyn Read + Send>) -> Self {
+        ResultStream {
+            reader: BufReader::new(reader),
+            state: State::Envelope,
+            _marker: PhantomData,
+        }
+    }
+
+    fn next_element(&mut self) -> Result<Option<T>, ClientError> {
+        if self.state == State::Envelope {
+            self.find_result()?;
+            self.state = State::First;
+        }
+
+        self.skip_whitespace()?;
+        match (self.state, self.peek()?) {
+            (_, Some(b']')) => {
+                self.reader.consume(1);
+                return Ok(None);
+            }
+            (State::First, Some(_)) => {}
+            (State::Rest, Some(b',')) => {
+                self.reader.consume(1);
+                self.skip_whitespace()?;
+            }
+            _ => return Err(invalid("malformed result array")),
+        }
+
+        let element = serde_json::from_slice(&self.read_value()?)?;
+        self.state = State::Rest;
+        Ok(Some(element))
+    }
+
+    /// Reads the response object up to the opening bracket of its `result`
+    /// array, other members are skipped.
+    fn find_result(&mut self) -> Result<(), ClientError> {
+        self.skip_whitespace()?;
+        self.expect(b'{')?;
+
+        loop {
+            self.skip_whitespace()?;
+            let key: String = serde_json::from_slice(&self.read_value()?)?;
+            self.skip_whitespace()?;
+            self.expect(b':')?;
+            self.skip_whitespace()?;
+
+            match key.as_str() {
+                "result" if self.peek()? == Some(b'[') => {
+                    self.reader.consume(1);
+                    return Ok(());
+                }
+                "result" => {
+                    return match &self.read_value()?[..] {
+                        b"null" => Err(ClientError::NullResult),
+                        _ => Err(invalid("JSON-RPC result is not an array")),
+                    };
+                }
+                "error" => {
+                    let error: JsonRpcError = serde_json::from_slice(&self.read_value()?)?;
+                    return Err(ClientError::JsonRpc(error));
+                }
+                _ => {
+                    self.read_value()?;
+                }
+            }
+
+            self.skip_whitespace()?;
+            self.expect(b',')?;
+        }
+    }
+
+    /// Reads the raw bytes of the JSON value at the current position.
+    fn read_value(&mut self) -> Result<Vec<u8>, ClientError> {
+        let mut raw = Vec::new();
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+
+        while let Some(byte) = self.peek()? {
+            if in_string {
+                self.reader.consume(1);
+                raw.push(byte);
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    in_string = false;
+                    if depth == 0 {
+                        return Ok(raw);
+                    }
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' if depth == 0 => break,
+                b'}' | b']' => depth -= 1,
+                b',' | b' ' | b'\t' | b'\n' | b'\r' if depth == 0 => break,
+                _ => {}
+            }
+            self.reader.consume(1);
+            raw.push(byte);
+
+            if depth == 0 && matches!(byte, b'}' | b']') {
+                return Ok(raw);
+            }
+        }
+
+        if raw.is_empty() || depth > 0 || in_string {
+            return Err(invalid("truncated JSON-RPC response"));
+        }
+        Ok(raw)
+    }
+
+    fn expect(&mut self, want: u8) -> Result<(), ClientError> {
+        match self.peek()? {
+            Some(byte) if byte == want => {
+                self.reader.consume(1);
+                Ok(())
+            }
+            _ => Err(invalid("malformed JSON-RPC response")),
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), ClientError> {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek()? {
+            self.reader.consume(1);
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, ClientError> {
+        let buf = self.reader.fill_buf().map_err(ClientError::transport)?;
+        Ok(buf.first().copied())
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for ResultStream<T> {
+    type Item = Result<T, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state == State::Done {
+            return None;
+        }
+
+        let next = self.next_element().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.state = State::Done;
+        }
+        next
+    }
+}
+
+impl<T> fmt::Debug for ResultStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResultStream")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+fn invalid(message: &str) -> ClientError {
+    ClientError::parse(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(body: &str) -> ResultStream<serde_json::Value> {
+        ResultStream::new(Box::new(io::Cursor::new(body.as_bytes().to_vec())))
+    }
+
+    #[test]
+    fn yields_each_element() {
+        let body = r#" { "jsonrpc": "2.0", "id": "1",
+            "result": [ {"a": "]}\"", "b": [1, {}]}, "x", 3, null, [] ] } "#;
+
+        let elements = stream(body).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(elements, vec![
+            serde_json::json!({"a": "]}\"", "b": [1, {}]}),
+            serde_json::json!("x"),
+            serde_json::json!(3),
+            serde_json::Value::Null,
+            serde_json::json!([]),
+        ]);
+    }
+
+    #[test]
+    fn members_before_result_are_skipped() {
+        let body = r#"{"id":{"nested":["]"]},"jsonrpc":"2.0","result":[]}"#;
+        assert_eq!(stream(body).count(), 0);
+    }
+
+    #[test]
+    fn error_response_is_an_error() {
+        let body = r#"{"jsonrpc":"2.0","id":"1","error":{"code":-32005,"message":"too many"}}"#;
+
+        let results = stream(body).collect::<Vec<_>>();
+        assert_eq!(results.len(), 1);
+        match &results[0] {
+            Err(ClientError::JsonRpc(e)) => assert_eq!(e.code, -32005),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn null_result_is_an_error() {
+        let body = r#"{"jsonrpc":"2.0","id":"1","result":null}"#;
+        assert!(matches!(
+            stream(body).next(),
+            Some(Err(ClientError::NullResult))
+        ));
+    }
+
+    #[test]
+    fn truncated_response_ends_with_an_error() {
+        let body = r#"{"jsonrpc":"2.0","id":"1","result":[1,{"a":"#;
+
+        let results = stream(body).collect::<Vec<_>>();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), &serde_json::json!(1));
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,79 @@
+//! Records JSON-RPC interactions to a file and replays them later without
+//! network access, similar to HTTP VCR libraries.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::ClientError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Mode {
+    /// Send requests to the node and save each response.
+    Record,
+    /// Serve responses from the cassette, never touching the network.
+    Replay,
+}
+
+/// A file of recorded responses keyed by the serialized request.
+#[derive(Debug)]
+pub(super) struct Cassette {
+    mode: Mode,
+    path: PathBuf,
+    interactions: Mutex<BTreeMap<String, serde_json::Value>>,
+}
+
+impl Cassette {
+    /// Starts a new recording, the file at `path` is overwritten on the first
+    /// request.
+    pub(super) fn record(path: PathBuf) -> Self {
+        Cassette {
+            mode: Mode::Record,
+            path,
+            interactions: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Loads a previous recording from `path`.
+    pub(super) fn replay(path: &Path) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        let interactions = serde_json::from_reader(io::BufReader::new(file))?;
+
+        Ok(Cassette {
+            mode: Mode::Replay,
+            path: path.to_owned(),
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    pub(super) fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns the recorded response body for `request`.
+    pub(super) fn response(&self, request: &str) -> Result<String, ClientError> {
+        let interactions = self.interactions.lock().expect("lock is not poisoned");
+
+        match interactions.get(request) {
+            Some(response) => Ok(response.to_string()),
+            None => Err(ClientError::transport(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no recorded response for request: {}", request),
+            ))),
+        }
+    }
+
+    /// Saves `response` as the response to `request` and writes the cassette
+    /// to disk.
+    pub(super) fn save(&self, request: &str, response: &str) -> Result<(), ClientError> {
+        let response: serde_json::Value = serde_json::from_str(response)?;
+
+        let mut interactions = self.interactions.lock().expect("lock is not poisoned");
+        interactions.insert(request.to_owned(), response);
+
+        let json = serde_json::to_string_pretty(&*interactions)?;
+        fs::write(&self.path, json).map_err(ClientError::transport)
+    }
+}
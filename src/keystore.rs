@@ -0,0 +1,503 @@
+//! Web3 Secret Storage (geth keystore) files.
+//! ref: https://github.com/ethereum/wiki/wiki/Web3-Secret-Storage-Definition
+
+use aes::Aes128;
+use ctr::cipher::{NewCipher, StreamCipher};
+use hmac::Hmac;
+use rand::Rng;
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::{address_from_secret_key, keccak256};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+/// Length in bytes of the derived key, the first half is the AES key and the
+/// second half the MAC key.
+const DERIVED_KEY_LEN: usize = 32;
+
+/// Most memory, in bytes, scrypt may use (`128 * n * r`), four times geth's
+/// standard parameters. Limits the cost of decrypting a hostile keystore.
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+
+/// Largest scrypt parallelization parameter accepted.
+const MAX_SCRYPT_P: u32 = 16;
+
+/// Largest pbkdf2 iteration count accepted, geth uses 262144.
+const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+
+/// Error returned when a keystore cannot be decrypted or encrypted.
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("failed to parse keystore")]
+    InvalidJson(#[source] serde_json::Error),
+    #[error("unsupported keystore version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("unsupported cipher: {0}")]
+    UnsupportedCipher(String),
+    #[error("invalid IV length: {0} bytes")]
+    InvalidIv(usize),
+    #[error("unsupported key derivation: {0}")]
+    UnsupportedKdf(String),
+    #[error("key derivation parameters too expensive: {0}")]
+    KdfParamsTooLarge(String),
+    #[error("invalid scrypt parameters")]
+    InvalidScryptParams,
+    #[error("keystore MAC mismatch, wrong password?")]
+    MacMismatch,
+    #[error("invalid secret key")]
+    InvalidKey(#[source] secp256k1::Error),
+}
+
+/// Decrypts a version 3 keystore file using `password`.
+///
+/// Both the scrypt and pbkdf2 key derivation functions are supported, the
+/// MAC is checked before the key is decrypted.
+pub fn decrypt_keystore(json: &str, password: &str) -> Result<SecretKey, KeystoreError> {
+    let keystore: Keystore = serde_json::from_str(json).map_err(KeystoreError::InvalidJson)?;
+    if keystore.version != 3 {
+        return Err(KeystoreError::UnsupportedVersion(keystore.version));
+    }
+
+    let crypto = keystore.crypto;
+    if crypto.cipher != "aes-128-ctr" {
+        return Err(KeystoreError::UnsupportedCipher(crypto.cipher));
+    }
+
+    let derived_key = crypto.kdf.derive_key(password)?;
+
+    let mac = keystore_mac(&derived_key, &crypto.ciphertext);
+    if !constant_time_eq(&mac, &crypto.mac) {
+        return Err(KeystoreError::MacMismatch);
+    }
+
+    let mut key = crypto.ciphertext;
+    apply_cipher(&derived_key, &crypto.cipherparams.iv, &mut key)?;
+    SecretKey::from_slice(&key).map_err(KeystoreError::InvalidKey)
+}
+
+/// Scrypt parameters used when encrypting a keystore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScryptParams {
+    /// CPU/memory cost, must be a power of two.
+    pub n: u32,
+    /// Block size.
+    pub r: u32,
+    /// Parallelization.
+    pub p: u32,
+}
+
+impl ScryptParams {
+    /// The parameters geth uses by default.
+    pub fn standard() -> Self {
+        ScryptParams {
+            n: 1 << 18,
+            r: 8,
+            p: 1,
+        }
+    }
+
+    /// The parameters geth uses with `--lightkdf`, cheap enough for tests.
+    pub fn light() -> Self {
+        ScryptParams {
+            n: 1 << 12,
+            r: 8,
+            p: 6,
+        }
+    }
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Encrypts `sk` with `password`, returns a version 3 keystore file.
+///
+/// The key is derived using scrypt with `params`, a fresh random salt and IV
+/// are used for every call.
+pub fn encrypt_keystore(
+    sk: &SecretKey,
+    password: &str,
+    params: ScryptParams,
+) -> Result<String, KeystoreError> {
+    let mut rng = rand::thread_rng();
+    let salt: [u8; 32] = rng.gen();
+    let iv: [u8; 16] = rng.gen();
+
+    let kdf = Kdf::Scrypt(ScryptKdfParams {
+        dklen: DERIVED_KEY_LEN,
+        n: params.n,
+        r: params.r,
+        p: params.p,
+        salt: salt.to_vec(),
+    });
+    let derived_key = kdf.derive_key(password)?;
+
+    let mut ciphertext = sk[..].to_vec();
+    apply_cipher(&derived_key, &iv, &mut ciphertext)?;
+    let mac = keystore_mac(&derived_key, &ciphertext).to_vec();
+
+    let address = address_from_secret_key(sk);
+    let keystore = Keystore {
+        address: Some(hex::encode(address.as_bytes())),
+        crypto: Crypto {
+            cipher: "aes-128-ctr".to_owned(),
+            cipherparams: CipherParams { iv: iv.to_vec() },
+            ciphertext,
+            kdf,
+            mac,
+        },
+        id: Some(random_uuid(&mut rng)),
+        version: 3,
+    };
+    let json = serde_json::to_string(&keystore).expect("keystore serializes to JSON");
+
+    Ok(json)
+}
+
+/// Formats 16 random bytes as a version 4 UUID.
+fn random_uuid<R: Rng>(rng: &mut R) -> String {
+    let mut bytes: [u8; 16] = rng.gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Keystore {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(alias = "Crypto")]
+    crypto: Crypto,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    version: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Crypto {
+    cipher: String,
+    cipherparams: CipherParams,
+    #[serde(with = "hex")]
+    ciphertext: Vec<u8>,
+    #[serde(flatten)]
+    kdf: Kdf,
+    #[serde(with = "hex")]
+    mac: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CipherParams {
+    #[serde(with = "hex")]
+    iv: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+enum Kdf {
+    Scrypt(ScryptKdfParams),
+    Pbkdf2(Pbkdf2KdfParams),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ScryptKdfParams {
+    dklen: usize,
+    n: u32,
+    r: u32,
+    p: u32,
+    #[serde(with = "hex")]
+    salt: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Pbkdf2KdfParams {
+    c: u32,
+    dklen: usize,
+    prf: String,
+    #[serde(with = "hex")]
+    salt: Vec<u8>,
+}
+
+impl Kdf {
+    fn derive_key(&self, password: &str) -> Result<Vec<u8>, KeystoreError> {
+        let key = match self {
+            Kdf::Scrypt(params) => {
+                if params.dklen != DERIVED_KEY_LEN {
+                    return Err(unsupported_key_length(params.dklen));
+                }
+                if !params.n.is_power_of_two() {
+                    return Err(KeystoreError::InvalidScryptParams);
+                }
+                let memory = 128 * u64::from(params.n) * u64::from(params.r);
+                if memory > MAX_SCRYPT_MEMORY || params.p > MAX_SCRYPT_P {
+                    return Err(KeystoreError::KdfParamsTooLarge(format!(
+                        "scrypt n={}, r={}, p={}",
+                        params.n, params.r, params.p
+                    )));
+                }
+                #[allow(clippy::cast_possible_truncation)] // log2 of a u32 fits in a u8.
+                let log_n = params.n.trailing_zeros() as u8;
+                let scrypt_params = scrypt::Params::new(log_n, params.r, params.p)
+                    .map_err(|_| KeystoreError::InvalidScryptParams)?;
+
+                let mut key = vec![0u8; params.dklen];
+                scrypt::scrypt(password.as_bytes(), &params.salt, &scrypt_params, &mut key)
+                    .map_err(|_| unsupported_key_length(params.dklen))?;
+                key
+            }
+            Kdf::Pbkdf2(params) => {
+                if params.dklen != DERIVED_KEY_LEN {
+                    return Err(unsupported_key_length(params.dklen));
+                }
+                if params.prf != "hmac-sha256" {
+                    return Err(KeystoreError::UnsupportedKdf(format!(
+                        "pbkdf2 prf {}",
+                        params.prf
+                    )));
+                }
+                if params.c > MAX_PBKDF2_ROUNDS {
+                    return Err(KeystoreError::KdfParamsTooLarge(format!(
+                        "pbkdf2 iteration count {}",
+                        params.c
+                    )));
+                }
+
+                let mut key = vec![0u8; params.dklen];
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(
+                    password.as_bytes(),
+                    &params.salt,
+                    params.c,
+                    &mut key,
+                );
+                key
+            }
+        };
+
+        Ok(key)
+    }
+}
+
+fn unsupported_key_length(dklen: usize) -> KeystoreError {
+    KeystoreError::UnsupportedKdf(format!("derived key length {}", dklen))
+}
+
+/// The MAC is the keccak256 hash of the second 16 bytes of the derived key
+/// followed by the ciphertext.
+fn keystore_mac(derived_key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut preimage = derived_key[16..32].to_vec();
+    preimage.extend_from_slice(ciphertext);
+    keccak256(&preimage)
+}
+
+/// Compares `a` and `b` without short-circuiting on the first differing
+/// byte, so checking a MAC does not leak how much of it matched. Only the
+/// lengths are compared in variable time.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// AES-128-CTR is symmetric, this both encrypts and decrypts `data` in place.
+fn apply_cipher(derived_key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), KeystoreError> {
+    if iv.len() != 16 {
+        return Err(KeystoreError::InvalidIv(iv.len()));
+    }
+
+    let mut cipher = Aes128Ctr::new(derived_key[..16].into(), iv.into());
+    cipher.apply_keystream(data);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{secret_key_from_hex, Address};
+
+    // The pbkdf2 vector is from the Web3 Secret Storage Definition.
+    const PASSWORD: &str = "testpassword";
+    const SECRET_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+    const ADDRESS: &str = "0x008aeeda4d805471df9b2a5b0f38a0c3bcba786b";
+
+    const PBKDF2_KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    // The scrypt vector in the definition uses `n = 2^18, r = 1` which violates
+    // the RFC 7914 requirement `n < 2^(16 * r)` enforced by the `scrypt`
+    // crate, this is `very-light-scrypt.json` from go-ethereum's
+    // `accounts/keystore/testdata`, encrypted with an empty password.
+    const SCRYPT_KEYSTORE: &str = r#"{
+        "address": "45dea0fb0bba44f4fcf290bba71fd57d7117cbb8",
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "ciphertext": "b87781948a1befd247bff51ef4063f716cf6c2d3481163e9a8f42e1f9bb74145",
+            "cipherparams": { "iv": "dc4926b48a105133d2f16b96833abf1e" },
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 2,
+                "p": 1,
+                "r": 8,
+                "salt": "004244bbdc51cadda545b1cfa43cff9ed2ae88e08c61f1479dbb45410722f8f0"
+            },
+            "mac": "39990c1684557447940d4c69e06b1b82b2aceacb43f284df65c956daf3046b85"
+        },
+        "id": "ce541d8d-c79b-40f8-9f8c-20f59616faba",
+        "version": 3
+    }"#;
+
+    #[test]
+    fn can_decrypt_pbkdf2_keystore() {
+        let sk = decrypt_keystore(PBKDF2_KEYSTORE, PASSWORD).unwrap();
+        assert_eq!(hex::encode(&sk[..]), SECRET_KEY);
+
+        let address = address_from_secret_key(&sk);
+        assert_eq!(address, Address::from_str(ADDRESS).unwrap());
+    }
+
+    #[test]
+    fn can_decrypt_scrypt_keystore() {
+        let sk = decrypt_keystore(SCRYPT_KEYSTORE, "").unwrap();
+
+        let address = address_from_secret_key(&sk);
+        assert_eq!(
+            address,
+            Address::from_str("0x45dea0fb0bba44f4fcf290bba71fd57d7117cbb8").unwrap()
+        );
+        assert!(decrypt_keystore(SCRYPT_KEYSTORE, PASSWORD).is_err());
+    }
+
+    #[test]
+    fn wrong_password_fails_mac_check() {
+        let res = decrypt_keystore(PBKDF2_KEYSTORE, "wrongpassword");
+        assert!(matches!(res, Err(KeystoreError::MacMismatch)));
+    }
+
+    #[test]
+    fn malformed_keystore_is_rejected() {
+        let res = decrypt_keystore("{}", PASSWORD);
+        assert!(matches!(res, Err(KeystoreError::InvalidJson(_))));
+    }
+
+    /// Returns `keystore` with `kdfparams.param` set to `value`.
+    fn with_kdf_param(keystore: &str, param: &str, value: serde_json::Value) -> String {
+        let mut json: serde_json::Value = serde_json::from_str(keystore).unwrap();
+        json["crypto"]["kdfparams"][param] = value;
+        json.to_string()
+    }
+
+    #[test]
+    fn unsupported_key_length_is_rejected() {
+        for keystore in &[PBKDF2_KEYSTORE, SCRYPT_KEYSTORE] {
+            let json = with_kdf_param(keystore, "dklen", serde_json::json!(1u64 << 40));
+            let err = decrypt_keystore(&json, PASSWORD).unwrap_err();
+            assert!(matches!(err, KeystoreError::UnsupportedKdf(_)), "{}", err);
+            assert!(err.to_string().contains("derived key length"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn expensive_kdf_parameters_are_rejected() {
+        let json = with_kdf_param(SCRYPT_KEYSTORE, "n", serde_json::json!(1u32 << 31));
+        let err = decrypt_keystore(&json, PASSWORD).unwrap_err();
+        assert!(
+            matches!(err, KeystoreError::KdfParamsTooLarge(_)),
+            "{}",
+            err
+        );
+
+        let json = with_kdf_param(SCRYPT_KEYSTORE, "p", serde_json::json!(u32::MAX));
+        let err = decrypt_keystore(&json, PASSWORD).unwrap_err();
+        assert!(
+            matches!(err, KeystoreError::KdfParamsTooLarge(_)),
+            "{}",
+            err
+        );
+
+        let json = with_kdf_param(PBKDF2_KEYSTORE, "c", serde_json::json!(u32::MAX));
+        let err = decrypt_keystore(&json, PASSWORD).unwrap_err();
+        assert!(
+            matches!(err, KeystoreError::KdfParamsTooLarge(_)),
+            "{}",
+            err
+        );
+        assert!(err.to_string().contains("iteration count"), "{}", err);
+    }
+
+    #[test]
+    fn encrypt_then_decrypt_round_trips() {
+        let sk = secret_key_from_hex(SECRET_KEY).unwrap();
+
+        let json = encrypt_keystore(&sk, PASSWORD, ScryptParams::light()).unwrap();
+        let decrypted = decrypt_keystore(&json, PASSWORD).unwrap();
+
+        assert_eq!(decrypted, sk);
+        assert!(decrypt_keystore(&json, "wrongpassword").is_err());
+    }
+
+    #[test]
+    fn encrypt_rejects_invalid_scrypt_params() {
+        let sk = secret_key_from_hex(SECRET_KEY).unwrap();
+        let params = ScryptParams {
+            n: 3,
+            ..ScryptParams::light()
+        };
+
+        let res = encrypt_keystore(&sk, PASSWORD, params);
+        assert!(matches!(res, Err(KeystoreError::InvalidScryptParams)));
+    }
+
+    #[test]
+    fn encrypted_keystore_has_v3_shape() {
+        let sk = secret_key_from_hex(SECRET_KEY).unwrap();
+
+        let json = encrypt_keystore(&sk, PASSWORD, ScryptParams::light()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["version"], 3);
+        assert_eq!(value["address"], &ADDRESS[2..]);
+        assert_eq!(value["crypto"]["cipher"], "aes-128-ctr");
+        assert_eq!(value["crypto"]["kdf"], "scrypt");
+        assert_eq!(value["crypto"]["kdfparams"]["n"], 4096);
+        assert_eq!(value["id"].as_str().unwrap().len(), 36);
+    }
+
+    #[test]
+    fn encryption_uses_fresh_salt_and_iv() {
+        let sk = secret_key_from_hex(SECRET_KEY).unwrap();
+
+        let a = encrypt_keystore(&sk, PASSWORD, ScryptParams::light()).unwrap();
+        let b = encrypt_keystore(&sk, PASSWORD, ScryptParams::light()).unwrap();
+
+        assert_ne!(a, b);
+    }
+}
@@ -13,16 +13,61 @@
 #![cfg_attr(not(test), warn(clippy::unwrap_used))]
 #![forbid(unsafe_code)]
 
-pub use clarity::Address;
-use secp256k1::Secp256k1;
+pub use crate::types::Address;
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
 pub use secp256k1::{PublicKey, SecretKey};
+use thiserror::Error;
 
 pub mod api;
+#[cfg(feature = "clarity")]
+pub mod convert;
+pub mod ens;
+pub mod gas;
+pub mod hd;
 pub mod jsonrpc;
+pub mod keystore;
+pub mod nonce;
+pub mod pending;
+pub mod rlp;
+pub mod transaction;
 pub mod types;
+pub mod watch;
+
+#[cfg(test)]
+mod test_util;
+
+/// Error returned when parsing a hex encoded secret key.
+#[derive(Clone, Copy, Debug, Error, PartialEq)]
+pub enum ParseSecretKeyError {
+    #[error("invalid secret key length: expected 64 hex characters, got {0}")]
+    Length(usize),
+    #[error("secret key is not valid hex")]
+    Hex(#[source] hex::FromHexError),
+    #[error("invalid secret key")]
+    Invalid(#[source] secp256k1::Error),
+}
+
+/// Parses a secret key from a hex string, the `0x` prefix is optional.
+pub fn secret_key_from_hex(s: &str) -> Result<SecretKey, ParseSecretKeyError> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() != 64 {
+        return Err(ParseSecretKeyError::Length(s.len()));
+    }
+
+    let bytes = hex::decode(s).map_err(ParseSecretKeyError::Hex)?;
+    SecretKey::from_slice(&bytes).map_err(ParseSecretKeyError::Invalid)
+}
+
+/// Gets the address of a hex encoded private key, the `0x` prefix is
+/// optional.
+pub fn address_from_hex_secret_key(s: &str) -> Result<Address, ParseSecretKeyError> {
+    let sk = secret_key_from_hex(s)?;
+    Ok(address_from_secret_key(&sk))
+}
 
 /// Gets the address of a private key.
-pub fn address_from_secret_key(sk: &SecretKey) -> Result<Address, clarity::Error> {
+pub fn address_from_secret_key(sk: &SecretKey) -> Address {
     let secp = Secp256k1::signing_only();
     let pk = PublicKey::from_secret_key(&secp, sk);
     address_from_public_key(&pk)
@@ -35,7 +80,7 @@ pub fn address_from_secret_key(sk: &SecretKey) -> Result<Address, clarity::Error
 /// crate is 65 bytes long, that is because it is prefixed by `0x04` to
 /// indicate an uncompressed public key; this first byte is ignored when
 /// computing the hash.
-pub fn address_from_public_key(pk: &PublicKey) -> Result<Address, clarity::Error> {
+pub fn address_from_public_key(pk: &PublicKey) -> Address {
     let pk = pk.serialize_uncompressed();
 
     debug_assert_eq!(pk[0], 0x04);
@@ -44,6 +89,64 @@ pub fn address_from_public_key(pk: &PublicKey) -> Result<Address, clarity::Error
     Address::from_slice(&hash[12..])
 }
 
+/// Hashes `message` as per EIP-191 (version `0x45`), the hash signed by
+/// `eth_sign` and `personal_sign`:
+/// `keccak256("\x19Ethereum Signed Message:\n" + len(message) + message)`.
+pub fn hash_personal_message(message: &[u8]) -> [u8; 32] {
+    let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    data.extend_from_slice(message);
+
+    keccak256(&data)
+}
+
+/// Error returned when an address cannot be recovered from a signature.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum RecoverError {
+    #[error("invalid signature length: expected 65 bytes, got {0}")]
+    Length(usize),
+    #[error("invalid signature recovery id: {0}")]
+    RecoveryId(u8),
+    #[error("invalid signature")]
+    Signature(#[source] secp256k1::Error),
+}
+
+/// Recovers the address that produced `signature` over `hash`.
+///
+/// `signature` is 65 bytes, `r || s || v`, where `v` is either the recovery
+/// id (0 or 1) or the recovery id plus 27 as returned by `eth_sign`.
+pub fn recover_address(hash: &[u8; 32], signature: &[u8]) -> Result<Address, RecoverError> {
+    if signature.len() != 65 {
+        return Err(RecoverError::Length(signature.len()));
+    }
+
+    let v = match signature[64] {
+        v @ 0..=1 => v,
+        v @ 27..=28 => v - 27,
+        v => return Err(RecoverError::RecoveryId(v)),
+    };
+    let recovery_id = RecoveryId::from_i32(i32::from(v)).expect("recovery id is 0 or 1");
+    let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id)
+        .map_err(RecoverError::Signature)?;
+    let message = Message::from_slice(hash).expect("32 byte hash");
+
+    let pk = Secp256k1::verification_only()
+        .recover(&message, &signature)
+        .map_err(RecoverError::Signature)?;
+    Ok(address_from_public_key(&pk))
+}
+
+/// Returns true if `signature` is a `personal_sign` (EIP-191) signature of
+/// `message` by `expected`, e.g. to check a login-by-signature.
+pub fn verify_personal_sign(message: &[u8], signature: &[u8; 65], expected: Address) -> bool {
+    let hash = hash_personal_message(message);
+    let signer = match recover_address(&hash, signature) {
+        Ok(signer) => signer,
+        Err(_) => return false,
+    };
+
+    signer == expected
+}
+
 /// Compute the Keccak-256 hash of input bytes.
 pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
     use tiny_keccak::{Hasher, Keccak};
@@ -53,3 +156,143 @@ pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
     hasher.finalize(&mut output);
     output
 }
+
+/// Returns the 4 byte selector of a function `signature`, e.g.
+/// `"transfer(address,uint256)"`, used as the start of the call data.
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    // Alice's key from the Infura integration tests.
+    const ALICE_KEY: &str = "aaaaaaaa6422720fab7fee3f875fc2cb399af859156d8f189b930e485674e472";
+    const ALICE_ADDRESS: &str = "0x0EB44ea45B049fc225Cfdf07883dD89C7FeBd8f0";
+
+    #[test]
+    fn can_get_address_from_hex_secret_key() {
+        let want = Address::from_str(ALICE_ADDRESS).unwrap();
+
+        let got = address_from_hex_secret_key(ALICE_KEY).unwrap();
+        assert_eq!(got, want);
+
+        let prefixed = format!("0x{}", ALICE_KEY);
+        let got = address_from_hex_secret_key(&prefixed).unwrap();
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn secret_key_from_hex_rejects_wrong_length() {
+        assert_eq!(
+            secret_key_from_hex(&ALICE_KEY[2..]),
+            Err(ParseSecretKeyError::Length(62))
+        );
+        assert_eq!(
+            secret_key_from_hex(&format!("{}00", ALICE_KEY)),
+            Err(ParseSecretKeyError::Length(66))
+        );
+        assert_eq!(secret_key_from_hex(""), Err(ParseSecretKeyError::Length(0)));
+        assert_eq!(
+            secret_key_from_hex("0x"),
+            Err(ParseSecretKeyError::Length(0))
+        );
+    }
+
+    #[test]
+    fn secret_key_from_hex_rejects_invalid_hex() {
+        let invalid = format!("zz{}", &ALICE_KEY[2..]);
+        assert!(matches!(
+            secret_key_from_hex(&invalid),
+            Err(ParseSecretKeyError::Hex(_))
+        ));
+    }
+
+    // Example from the web3.js `eth.accounts.sign` documentation.
+    const WEB3_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const WEB3_ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+    const WEB3_SIGNATURE: &str = "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c";
+
+    #[test]
+    fn can_hash_personal_message() {
+        assert_eq!(
+            hex::encode(hash_personal_message(b"Some data")),
+            "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+        );
+    }
+
+    fn web3_signature() -> [u8; 65] {
+        let mut signature = [0u8; 65];
+        signature.copy_from_slice(&hex::decode(WEB3_SIGNATURE).unwrap());
+        signature
+    }
+
+    #[test]
+    fn can_verify_personal_sign() {
+        let expected = Address::from_str(WEB3_ADDRESS).unwrap();
+
+        assert!(verify_personal_sign(
+            b"Some data",
+            &web3_signature(),
+            expected
+        ));
+    }
+
+    #[test]
+    fn personal_sign_rejects_tampered_message_or_signer() {
+        let expected = Address::from_str(WEB3_ADDRESS).unwrap();
+        let signature = web3_signature();
+
+        assert!(!verify_personal_sign(b"Some date", &signature, expected));
+        let alice = Address::from_str(ALICE_ADDRESS).unwrap();
+        assert!(!verify_personal_sign(b"Some data", &signature, alice));
+
+        let mut invalid = signature;
+        invalid[64] = 0x42;
+        assert!(!verify_personal_sign(b"Some data", &invalid, expected));
+    }
+
+    #[test]
+    fn can_recover_address() {
+        let hash = hash_personal_message(b"Some data");
+        let signature = hex::decode(WEB3_SIGNATURE).unwrap();
+
+        let got = recover_address(&hash, &signature).unwrap();
+        assert_eq!(got, Address::from_str(WEB3_ADDRESS).unwrap());
+        assert_eq!(got, address_from_hex_secret_key(WEB3_KEY).unwrap());
+
+        // Recovery id without the +27 offset.
+        let mut signature = signature;
+        signature[64] -= 27;
+        assert_eq!(recover_address(&hash, &signature).unwrap(), got);
+    }
+
+    #[test]
+    fn recover_address_rejects_invalid_signatures() {
+        let hash = hash_personal_message(b"Some data");
+        let signature = hex::decode(WEB3_SIGNATURE).unwrap();
+
+        assert_eq!(
+            recover_address(&hash, &signature[..64]),
+            Err(RecoverError::Length(64))
+        );
+
+        let mut bad_v = signature;
+        bad_v[64] = 2;
+        assert_eq!(
+            recover_address(&hash, &bad_v),
+            Err(RecoverError::RecoveryId(2))
+        );
+    }
+
+    #[test]
+    fn can_compute_function_selector() {
+        assert_eq!(function_selector("transfer(address,uint256)"), [
+            0xa9, 0x05, 0x9c, 0xbb
+        ]);
+    }
+}
@@ -0,0 +1,85 @@
+//! Hands out transaction nonces locally so several transactions can be sent
+//! in quick succession without waiting for the node to see each one.
+
+use std::sync::Mutex;
+
+use crate::api::{Client, ClientError};
+use crate::types::{Address, BlockNumber};
+
+/// Hands out monotonically increasing nonces for an account.
+///
+/// The first nonce is read from the node using the `pending` block, after
+/// that nonces are incremented locally. Call `reset` to resync with the node,
+/// e.g. after a transaction failed to send.
+#[derive(Debug)]
+pub struct NonceManager {
+    client: Client,
+    address: Address,
+    next: Mutex<Option<u64>>,
+}
+
+impl NonceManager {
+    pub fn new(client: Client, address: Address) -> Self {
+        NonceManager {
+            client,
+            address,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Returns the nonce to use for the next transaction.
+    pub fn next(&self) -> Result<u64, ClientError> {
+        let mut next = self.next.lock().expect("lock is not poisoned");
+
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => self
+                .client
+                .get_transaction_count(self.address, BlockNumber::Pending)?,
+        };
+        *next = Some(nonce + 1);
+
+        Ok(nonce)
+    }
+
+    /// Forgets the locally tracked nonce, the next call to `next` reads it
+    /// from the node again.
+    pub fn reset(&self) {
+        *self.next.lock().expect("lock is not poisoned") = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::MockServer;
+
+    #[test]
+    fn next_increments_locally() {
+        let server = MockServer::with_result(json!("0x5"));
+        let nonces = NonceManager::new(Client::new(server.url()), Address::default());
+
+        assert_eq!(nonces.next().unwrap(), 5);
+        assert_eq!(nonces.next().unwrap(), 6);
+        assert_eq!(nonces.next().unwrap(), 7);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].json()["params"][1], "pending");
+    }
+
+    #[test]
+    fn reset_resyncs_with_node() {
+        let server = MockServer::with_result(json!("0x5"));
+        let nonces = NonceManager::new(Client::new(server.url()), Address::default());
+
+        assert_eq!(nonces.next().unwrap(), 5);
+        assert_eq!(nonces.next().unwrap(), 6);
+
+        nonces.reset();
+        assert_eq!(nonces.next().unwrap(), 5);
+        assert_eq!(server.requests().len(), 2);
+    }
+}
@@ -0,0 +1,243 @@
+//! A handle to a sent transaction, for waiting until it is mined.
+
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::{Client, ClientError};
+use crate::types::{TransactionReceipt, H256};
+
+/// How often the node is polled by default.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for a receipt by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Cap of the polling backoff, as a multiple of the initial interval.
+const MAX_BACKOFF_FACTOR: u32 = 8;
+
+/// A transaction that has been sent to the node, returned by
+/// `Client::send_raw_transaction_pending`.
+pub struct PendingTransaction<'a> {
+    client: &'a Client,
+    hash: H256,
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl<'a> PendingTransaction<'a> {
+    pub fn new(client: &'a Client, hash: H256) -> Self {
+        PendingTransaction {
+            client,
+            hash,
+            interval: DEFAULT_INTERVAL,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Returns the transaction hash.
+    pub fn hash(&self) -> H256 {
+        self.hash
+    }
+
+    /// Sets the delay between the first two polls, defaults to one second.
+    /// The delay doubles after each poll, up to eight times `interval`.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how long `await_receipt` waits before giving up, defaults to five
+    /// minutes. A timeout too large to represent, e.g. `Duration::MAX`, means
+    /// wait forever.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Blocks until the transaction is mined and its block has
+    /// `confirmations` confirmations (the including block counts as one),
+    /// then returns the receipt.
+    ///
+    /// Returns `None` if this takes longer than the timeout.
+    pub fn await_receipt(
+        &self,
+        confirmations: u64,
+    ) -> Result<Option<TransactionReceipt>, ClientError> {
+        let deadline = Instant::now().checked_add(self.timeout);
+        let max_interval = self.interval.saturating_mul(MAX_BACKOFF_FACTOR);
+        let mut delay = self.interval;
+
+        loop {
+            if let Some(receipt) = self.client.get_transaction_receipt(self.hash)? {
+                if self.is_confirmed(&receipt, confirmations)? {
+                    return Ok(Some(receipt));
+                }
+            }
+
+            let sleep = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(None);
+                    }
+                    delay.min(remaining)
+                }
+                None => delay,
+            };
+            thread::sleep(sleep);
+            delay = delay.saturating_mul(2).min(max_interval);
+        }
+    }
+
+    /// The including block is the first confirmation, the chain head is only
+    /// fetched if more are needed.
+    fn is_confirmed(
+        &self,
+        receipt: &TransactionReceipt,
+        confirmations: u64,
+    ) -> Result<bool, ClientError> {
+        let mined = match receipt.block_number {
+            Some(mined) => mined.as_u64(),
+            None => return Ok(false),
+        };
+        if confirmations <= 1 {
+            return Ok(true);
+        }
+
+        let latest = self.client.block_number()?;
+        Ok(latest.saturating_add(1).saturating_sub(mined) >= confirmations)
+    }
+}
+
+impl fmt::Debug for PendingTransaction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingTransaction")
+            .field("hash", &self.hash)
+            .field("interval", &self.interval)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::{self, MockServer};
+
+    const HASH: &str = "0x422fb0d5953c0c48cbb42fb58e1c30f5e150441c68374d70ca7d4f191fd56f26";
+
+    fn receipt(block_number: u64) -> serde_json::Value {
+        json!({
+            "transactionHash": HASH,
+            "transactionIndex": "0x0",
+            "blockHash": "0x83eaba432089a0bfe99e9fc9022d1cfcb78f95f407821be81737c84ae0b439c5",
+            "blockNumber": format!("0x{:x}", block_number),
+            "cumulativeGasUsed": "0x5208",
+            "gasUsed": "0x5208",
+            "contractAddress": null,
+            "logs": [],
+            "status": "0x1",
+            "root": null,
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+        })
+    }
+
+    /// A node where the transaction is mined in block 16 once the receipt
+    /// has been requested twice, and a block is mined on every
+    /// `eth_blockNumber` call.
+    fn node() -> MockServer {
+        let receipt_calls = Arc::new(AtomicU64::new(0));
+        let block = Arc::new(AtomicU64::new(16));
+
+        MockServer::start(move |request| {
+            let result = match request.json()["method"].as_str() {
+                Some("eth_sendRawTransaction") => json!(HASH),
+                Some("eth_getTransactionReceipt") => {
+                    if receipt_calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                        json!(null)
+                    } else {
+                        receipt(16)
+                    }
+                }
+                Some("eth_blockNumber") => {
+                    json!(format!("0x{:x}", block.fetch_add(1, Ordering::SeqCst)))
+                }
+                _ => json!(null),
+            };
+            test_util::response(result)
+        })
+    }
+
+    #[test]
+    fn waits_for_receipt_and_confirmations() {
+        let server = node();
+        let client = Client::new(server.url());
+
+        let pending = client
+            .send_raw_transaction_pending("0xf86c".to_owned())
+            .unwrap()
+            .interval(Duration::from_millis(1));
+        assert_eq!(pending.hash(), HASH.parse().unwrap());
+
+        let receipt = pending.await_receipt(3).unwrap().unwrap();
+        assert_eq!(receipt.transaction_hash, pending.hash());
+
+        // Blocks 16, 17 and 18 were seen before three confirmations.
+        let block_number_calls = server
+            .requests()
+            .iter()
+            .filter(|request| request.json()["method"] == "eth_blockNumber")
+            .count();
+        assert_eq!(block_number_calls, 3);
+    }
+
+    #[test]
+    fn unbounded_timeout_does_not_overflow() {
+        let server = node();
+        let client = Client::new(server.url());
+
+        let receipt = PendingTransaction::new(&client, HASH.parse().unwrap())
+            .interval(Duration::from_millis(1))
+            .timeout(Duration::MAX)
+            .await_receipt(1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipt.transaction_hash, HASH.parse().unwrap());
+    }
+
+    #[test]
+    fn times_out_if_never_mined() {
+        let server = MockServer::with_result(json!(null));
+        let client = Client::new(server.url());
+
+        let receipt = PendingTransaction::new(&client, HASH.parse().unwrap())
+            .interval(Duration::from_millis(1))
+            .timeout(Duration::from_millis(20))
+            .await_receipt(1)
+            .unwrap();
+        assert!(receipt.is_none());
+    }
+
+    #[test]
+    fn latest_block_at_u64_max_does_not_overflow() {
+        let server = MockServer::start(|request| {
+            let result = match request.json()["method"].as_str() {
+                Some("eth_getTransactionReceipt") => receipt(16),
+                _ => json!(format!("0x{:x}", u64::MAX)),
+            };
+            test_util::response(result)
+        });
+        let client = Client::new(server.url());
+
+        let receipt = PendingTransaction::new(&client, HASH.parse().unwrap())
+            .await_receipt(2)
+            .unwrap();
+        assert!(receipt.is_some());
+    }
+}
@@ -0,0 +1,334 @@
+//! Recursive Length Prefix (RLP) encoding.
+//! ref: https://eth.wiki/fundamentals/rlp
+
+use std::convert::TryFrom;
+
+use thiserror::Error;
+
+use crate::types::{H160, H256, U256};
+
+/// Deepest list nesting accepted by `decode`, so hostile input cannot
+/// overflow the stack.
+const MAX_DEPTH: usize = 1024;
+
+/// An RLP item, either a byte string or a list of items.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RlpItem {
+    /// A byte string.
+    Bytes(Vec<u8>),
+    /// A list of items.
+    List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+    /// Encodes this item.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_to(&mut out);
+        out
+    }
+
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        match self {
+            RlpItem::Bytes(bytes) => {
+                if bytes.len() == 1 && bytes[0] < 0x80 {
+                    out.push(bytes[0]);
+                } else {
+                    encode_length(bytes.len(), 0x80, out);
+                    out.extend_from_slice(bytes);
+                }
+            }
+            RlpItem::List(items) => {
+                let mut payload = Vec::new();
+                for item in items {
+                    item.encode_to(&mut payload);
+                }
+                encode_length(payload.len(), 0xc0, out);
+                out.extend_from_slice(&payload);
+            }
+        }
+    }
+
+    /// Returns the bytes if this item is a byte string.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            RlpItem::Bytes(bytes) => Some(bytes),
+            RlpItem::List(_) => None,
+        }
+    }
+
+    /// Returns the items if this item is a list.
+    pub fn as_list(&self) -> Option<&[RlpItem]> {
+        match self {
+            RlpItem::Bytes(_) => None,
+            RlpItem::List(items) => Some(items),
+        }
+    }
+}
+
+impl From<Vec<u8>> for RlpItem {
+    fn from(bytes: Vec<u8>) -> Self {
+        RlpItem::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for RlpItem {
+    fn from(bytes: &[u8]) -> Self {
+        RlpItem::Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Vec<RlpItem>> for RlpItem {
+    fn from(items: Vec<RlpItem>) -> Self {
+        RlpItem::List(items)
+    }
+}
+
+/// Integers are encoded big-endian with no leading zeros, zero is the empty
+/// byte string.
+impl From<u64> for RlpItem {
+    fn from(n: u64) -> Self {
+        U256::from(n).into()
+    }
+}
+
+impl From<U256> for RlpItem {
+    fn from(n: U256) -> Self {
+        let mut buf = [0u8; 32];
+        n.to_big_endian(&mut buf);
+        let start = buf.iter().position(|b| *b != 0).unwrap_or(buf.len());
+
+        RlpItem::Bytes(buf[start..].to_vec())
+    }
+}
+
+impl From<H160> for RlpItem {
+    fn from(address: H160) -> Self {
+        RlpItem::Bytes(address.as_bytes().to_vec())
+    }
+}
+
+impl From<H256> for RlpItem {
+    fn from(hash: H256) -> Self {
+        RlpItem::Bytes(hash.as_bytes().to_vec())
+    }
+}
+
+/// Encodes `items` as an RLP list.
+pub fn encode(items: &[RlpItem]) -> Vec<u8> {
+    RlpItem::List(items.to_vec()).encode()
+}
+
+/// Decodes a single RLP item, `bytes` must contain exactly one item. Lists
+/// may be nested at most 1024 deep.
+pub fn decode(bytes: &[u8]) -> Result<RlpItem, DecodeError> {
+    let (item, rest) = decode_item(bytes, 0)?;
+    if !rest.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+
+    Ok(item)
+}
+
+/// Errors returned when decoding RLP.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    #[error("trailing bytes after RLP item")]
+    TrailingBytes,
+    #[error("non-canonical RLP encoding")]
+    NonCanonical,
+    #[error("RLP item length overflows usize")]
+    Overflow,
+    #[error("RLP lists nested too deeply")]
+    TooDeep,
+}
+
+/// Writes the prefix for a payload of `len` bytes, `offset` is 0x80 for byte
+/// strings and 0xc0 for lists.
+fn encode_length(len: usize, offset: u8, out: &mut Vec<u8>) {
+    if len <= 55 {
+        out.push(offset + u8::try_from(len).expect("len is at most 55"));
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let start = len_bytes.iter().position(|b| *b != 0).unwrap_or(0);
+        let len_bytes = &len_bytes[start..];
+
+        out.push(offset + 55 + u8::try_from(len_bytes.len()).expect("at most 8 bytes"));
+        out.extend_from_slice(len_bytes);
+    }
+}
+
+/// Decodes the item at the start of `bytes`, `depth` is the number of lists
+/// it is nested in.
+fn decode_item(bytes: &[u8], depth: usize) -> Result<(RlpItem, &[u8]), DecodeError> {
+    let (&prefix, rest) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+
+    match prefix {
+        0x00..=0x7f => Ok((RlpItem::Bytes(vec![prefix]), rest)),
+        0x80..=0xbf => {
+            let (payload, rest) = decode_payload(prefix - 0x80, rest)?;
+            if payload.len() == 1 && payload[0] < 0x80 {
+                return Err(DecodeError::NonCanonical);
+            }
+            Ok((RlpItem::Bytes(payload.to_vec()), rest))
+        }
+        0xc0..=0xff => {
+            if depth == MAX_DEPTH {
+                return Err(DecodeError::TooDeep);
+            }
+            let (mut payload, rest) = decode_payload(prefix - 0xc0, rest)?;
+
+            let mut items = Vec::new();
+            while !payload.is_empty() {
+                let (item, remaining) = decode_item(payload, depth + 1)?;
+                items.push(item);
+                payload = remaining;
+            }
+            Ok((RlpItem::List(items), rest))
+        }
+    }
+}
+
+/// Splits off the payload following a prefix, `marker` is the prefix with
+/// the byte string/list offset removed.
+fn decode_payload(marker: u8, bytes: &[u8]) -> Result<(&[u8], &[u8]), DecodeError> {
+    let (len, bytes) = if marker <= 55 {
+        (usize::from(marker), bytes)
+    } else {
+        let len_of_len = usize::from(marker - 55);
+        if bytes.len() < len_of_len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (len_bytes, bytes) = bytes.split_at(len_of_len);
+        if len_bytes[0] == 0 {
+            return Err(DecodeError::NonCanonical);
+        }
+        if len_of_len > std::mem::size_of::<usize>() {
+            return Err(DecodeError::Overflow);
+        }
+
+        let len = len_bytes
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+        if len <= 55 {
+            return Err(DecodeError::NonCanonical);
+        }
+        (len, bytes)
+    };
+
+    if bytes.len() < len {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+
+    Ok(bytes.split_at(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(s: &str) -> RlpItem {
+        RlpItem::Bytes(s.as_bytes().to_vec())
+    }
+
+    fn round_trip(item: RlpItem, encoded: &[u8]) {
+        assert_eq!(item.encode(), encoded);
+        assert_eq!(decode(encoded).unwrap(), item);
+    }
+
+    #[test]
+    fn empty_string() {
+        round_trip(bytes(""), &[0x80]);
+    }
+
+    #[test]
+    fn dog() {
+        round_trip(bytes("dog"), &[0x83, b'd', b'o', b'g']);
+    }
+
+    #[test]
+    fn cat_dog_list() {
+        let encoded = [0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g'];
+
+        assert_eq!(encode(&[bytes("cat"), bytes("dog")]), encoded);
+        round_trip(RlpItem::List(vec![bytes("cat"), bytes("dog")]), &encoded);
+    }
+
+    #[test]
+    fn empty_list() {
+        round_trip(RlpItem::List(vec![]), &[0xc0]);
+    }
+
+    #[test]
+    fn integers() {
+        round_trip(0u64.into(), &[0x80]);
+        round_trip(RlpItem::Bytes(vec![0x00]), &[0x00]);
+        round_trip(15u64.into(), &[0x0f]);
+        round_trip(1024u64.into(), &[0x82, 0x04, 0x00]);
+    }
+
+    #[test]
+    fn nested_lists() {
+        // The set theoretical representation of three: [ [], [[]], [ [], [[]] ] ]
+        let empty = || RlpItem::List(vec![]);
+        let item = RlpItem::List(vec![
+            empty(),
+            RlpItem::List(vec![empty()]),
+            RlpItem::List(vec![empty(), RlpItem::List(vec![empty()])]),
+        ]);
+
+        round_trip(item, &[0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0]);
+    }
+
+    #[test]
+    fn long_string() {
+        let s = "Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let mut encoded = vec![0xb8, 0x38];
+        encoded.extend_from_slice(s.as_bytes());
+
+        round_trip(bytes(s), &encoded);
+    }
+
+    #[test]
+    fn decode_rejects_invalid_input() {
+        assert_eq!(decode(&[]), Err(DecodeError::UnexpectedEnd));
+        assert_eq!(decode(&[0x83, b'd', b'o']), Err(DecodeError::UnexpectedEnd));
+        assert_eq!(decode(&[0x80, 0x80]), Err(DecodeError::TrailingBytes));
+        // Single byte below 0x80 must not be prefixed.
+        assert_eq!(decode(&[0x81, 0x05]), Err(DecodeError::NonCanonical));
+        // Short string using the long form.
+        assert_eq!(decode(&[0xb8, 0x01, 0x80]), Err(DecodeError::NonCanonical));
+    }
+
+    /// Returns the empty list nested in `depth` lists.
+    fn nested_empty_list(depth: usize) -> Vec<u8> {
+        // Prefixes from the innermost out, each covers everything inside it.
+        let mut prefixes = Vec::new();
+        let mut len = 1;
+        for _ in 0..depth {
+            let mut prefix = Vec::new();
+            encode_length(len, 0xc0, &mut prefix);
+            len += prefix.len();
+            prefixes.push(prefix);
+        }
+
+        let mut encoded = prefixes.into_iter().rev().flatten().collect::<Vec<_>>();
+        encoded.push(0xc0);
+        encoded
+    }
+
+    #[test]
+    fn decode_limits_nesting_depth() {
+        assert!(decode(&nested_empty_list(MAX_DEPTH - 1)).is_ok());
+        assert_eq!(
+            decode(&nested_empty_list(MAX_DEPTH)),
+            Err(DecodeError::TooDeep)
+        );
+        assert_eq!(
+            decode(&nested_empty_list(100_000)),
+            Err(DecodeError::TooDeep)
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Test utilities: a minimal HTTP server for exercising the JSON-RPC clients
+//! without a node.
+#![allow(dead_code)] // Not every helper is used by every test.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use url::Url;
+
+/// An HTTP request as seen by the mock server.
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    /// E.g. "POST / HTTP/1.1".
+    pub request_line: String,
+    /// Header names are lowercase.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Returns the value of header `name` (case insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.headers
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the request body parsed as JSON.
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("request body is JSON")
+    }
+}
+
+/// A server that answers every request with the body returned by its
+/// handler, one connection per request.
+#[derive(Debug)]
+pub struct MockServer {
+    url: Url,
+    requests: Receiver<RecordedRequest>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> String + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock server");
+        let addr = listener.local_addr().expect("mock server has an address");
+        let (tx, requests) = mpsc::channel();
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let accepted = Arc::clone(&connections);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let request = match read_request(&mut stream) {
+                    Some(request) => request,
+                    None => continue,
+                };
+
+                let body = handler(&request);
+                let _ = tx.send(request);
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: \
+                     {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let url = Url::parse(&format!("http://{}/", addr)).expect("valid url");
+        MockServer {
+            url,
+            requests,
+            connections,
+        }
+    }
+
+    /// Like `start` but keeps connections open for further requests, serving
+    /// each connection on its own thread.
+    pub fn start_keep_alive<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> String + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock server");
+        let addr = listener.local_addr().expect("mock server has an address");
+        let (tx, requests) = mpsc::channel();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let handler = Arc::new(handler);
+
+        let accepted = Arc::clone(&connections);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                accepted.fetch_add(1, Ordering::SeqCst);
+
+                let tx = tx.clone();
+                let handler = Arc::clone(&handler);
+                thread::spawn(move || {
+                    while let Some(request) = read_request(&mut stream) {
+                        let body = handler(&request);
+                        let _ = tx.send(request);
+
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: \
+                             {}\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        if stream.write_all(response.as_bytes()).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let url = Url::parse(&format!("http://{}/", addr)).expect("valid url");
+        MockServer {
+            url,
+            requests,
+            connections,
+        }
+    }
+
+    /// Starts a server that answers every request with `result`.
+    pub fn with_result(result: serde_json::Value) -> Self {
+        Self::start(move |_| response(result.clone()))
+    }
+
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Returns the requests received since the last call.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.try_iter().collect()
+    }
+
+    /// Returns the number of connections accepted so far.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+/// An HTTP `CONNECT` proxy that, rather than tunnelling to the target,
+/// answers the tunnelled requests itself.
+#[derive(Debug)]
+pub struct MockProxy {
+    url: Url,
+    connects: Receiver<RecordedRequest>,
+    requests: Receiver<RecordedRequest>,
+}
+
+impl MockProxy {
+    /// Starts a proxy that answers every tunnelled request with `result`.
+    pub fn with_result(result: serde_json::Value) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock proxy");
+        let addr = listener.local_addr().expect("mock proxy has an address");
+        let (tx, connects) = mpsc::channel();
+        let (requests_tx, requests) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let connect = match read_request(&mut stream) {
+                    Some(request) => request,
+                    None => continue,
+                };
+                let _ = tx.send(connect);
+                let _ = stream.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n");
+
+                match read_request(&mut stream) {
+                    Some(request) => {
+                        let _ = requests_tx.send(request);
+                    }
+                    None => continue,
+                }
+                let body = response(result.clone());
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: \
+                     {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let url = Url::parse(&format!("http://{}", addr)).expect("valid url");
+        MockProxy {
+            url,
+            connects,
+            requests,
+        }
+    }
+
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Returns the `CONNECT` requests received since the last call.
+    pub fn connects(&self) -> Vec<RecordedRequest> {
+        self.connects.try_iter().collect()
+    }
+
+    /// Returns the `host:port` targets of the `CONNECT` requests received
+    /// since the last call.
+    pub fn targets(&self) -> Vec<String> {
+        self.connects()
+            .iter()
+            .map(|connect| {
+                connect
+                    .request_line
+                    .strip_prefix("CONNECT ")
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .unwrap_or_default()
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    /// Returns the requests tunnelled through the proxy since the last call.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.try_iter().collect()
+    }
+}
+
+/// Wraps `result` in a JSON-RPC response.
+pub fn response(result: serde_json::Value) -> String {
+    serde_json::json!({ "jsonrpc": "2.0", "id": "1", "result": result }).to_string()
+}
+
+/// Returns a JSON-RPC error response.
+pub fn error_response(code: i64, message: &str) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": "1",
+        "error": { "code": code, "message": message }
+    })
+    .to_string()
+}
+
+fn read_request<R: Read>(stream: R) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).ok()? == 0 {
+        // Connection closed.
+        return None;
+    }
+    let request_line = request_line.trim_end().to_owned();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_lowercase(), value.trim().to_owned()));
+        }
+    }
+
+    let len = headers
+        .iter()
+        .find(|(key, _)| key == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(RecordedRequest {
+        request_line,
+        headers,
+        body: String::from_utf8(body).ok()?,
+    })
+}
@@ -0,0 +1,758 @@
+//! Building and signing transactions.
+//! ref: https://eips.ethereum.org/EIPS/eip-155
+//! ref: https://eips.ethereum.org/EIPS/eip-2930
+//! ref: https://eips.ethereum.org/EIPS/eip-1559
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use secp256k1::{Message, Secp256k1, SecretKey};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::keccak256;
+use crate::rlp::{self, RlpItem};
+use crate::types::{AccessList, Bytes, H160, H256, U256};
+
+/// Chain id used for replay protection (EIP-155), defaults to zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ChainId(u64);
+
+impl ChainId {
+    pub const MAINNET: ChainId = ChainId(1);
+    pub const ROPSTEN: ChainId = ChainId(3);
+    pub const RINKEBY: ChainId = ChainId(4);
+    pub const GOERLI: ChainId = ChainId(5);
+    pub const KOVAN: ChainId = ChainId(42);
+    pub const SEPOLIA: ChainId = ChainId(11_155_111);
+
+    /// Names accepted by `from_str`, all lowercase.
+    const NAMES: [(&'static str, ChainId); 6] = [
+        ("mainnet", ChainId::MAINNET),
+        ("ropsten", ChainId::ROPSTEN),
+        ("rinkeby", ChainId::RINKEBY),
+        ("goerli", ChainId::GOERLI),
+        ("kovan", ChainId::KOVAN),
+        ("sepolia", ChainId::SEPOLIA),
+    ];
+
+    pub fn new(id: u64) -> Self {
+        ChainId(id)
+    }
+
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ChainId {
+    fn from(id: u64) -> Self {
+        ChainId(id)
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Parses a decimal chain id, e.g. "5", a hex chain id as returned by
+/// `eth_chainId`, e.g. "0x5", or a network name, e.g. "goerli" (case
+/// insensitive).
+impl FromStr for ChainId {
+    type Err = UnknownChain;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => s.parse::<u64>(),
+        };
+        if let Ok(id) = id {
+            return Ok(ChainId(id));
+        }
+
+        let name = s.to_lowercase();
+        ChainId::NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, id)| *id)
+            .ok_or_else(|| UnknownChain(s.to_owned()))
+    }
+}
+
+/// Serializes as a bare integer, e.g. `1`.
+impl Serialize for ChainId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+/// Deserializes from an integer, e.g. `1`, or a string accepted by
+/// `from_str`, e.g. `"1"` or `"0x1"`.
+impl<'de> Deserialize<'de> for ChainId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(u64),
+            String(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(id) => Ok(ChainId(id)),
+            Repr::String(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Error returned when parsing a `ChainId` from a string that is neither a
+/// number nor a known network name.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("unknown chain: {0}")]
+pub struct UnknownChain(pub String);
+
+/// Minimum percentage geth requires a replacement transaction to raise the
+/// gas price by.
+pub const MIN_FEE_BUMP_PERCENT: u8 = 10;
+
+/// Error returned when a fee cannot be bumped.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum FeeBumpError {
+    /// The bump is too small for the node to accept the replacement
+    /// transaction.
+    #[error("fee bump of {0}% is below the 10% minimum")]
+    TooSmall(u8),
+    /// The bumped fee does not fit in a `U256`.
+    #[error("bumped fee overflows")]
+    Overflow,
+}
+
+/// A legacy (pre EIP-2718) transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LegacyTransaction {
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas_limit: U256,
+    /// Recipient, `None` for contract creation.
+    pub to: Option<H160>,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+impl LegacyTransaction {
+    /// Returns the hash that is signed, this commits to `chain_id`.
+    pub fn signing_hash(&self, chain_id: ChainId) -> H256 {
+        let mut items = self.rlp_fields();
+        items.extend_from_slice(&[chain_id.as_u64().into(), 0u64.into(), 0u64.into()]);
+
+        H256::from(keccak256(&rlp::encode(&items)))
+    }
+
+    /// Returns the hash signed by transactions from before EIP-155, which
+    /// do not commit to a chain id.
+    pub fn unprotected_signing_hash(&self) -> H256 {
+        H256::from(keccak256(&rlp::encode(&self.rlp_fields())))
+    }
+
+    /// Signs the transaction with `sk`, returning the raw signed transaction
+    /// ready for `send_raw_transaction`.
+    ///
+    /// The signature is replay protected as per EIP-155, `v` is set to
+    /// `recovery_id + chain_id * 2 + 35`.
+    pub fn sign(&self, sk: &SecretKey, chain_id: ChainId) -> Bytes {
+        let hash = self.signing_hash(chain_id);
+        let (recovery_id, r, s) = sign_hash(&hash, sk);
+        // Computed in 256 bits so large chain ids cannot overflow.
+        let v = U256::from(chain_id.as_u64()) * U256::from(2) + U256::from(35 + recovery_id);
+
+        let mut items = self.rlp_fields();
+        items.extend_from_slice(&[v.into(), r.into(), s.into()]);
+
+        Bytes(rlp::encode(&items))
+    }
+
+    /// Returns a copy of the transaction with the gas price raised by
+    /// `by_percent`, for replacing a stuck transaction. The nonce is
+    /// unchanged, sign the result with the original key.
+    pub fn bump_fee(&self, by_percent: u8) -> Result<Self, FeeBumpError> {
+        Ok(LegacyTransaction {
+            gas_price: bump(self.gas_price, by_percent)?,
+            ..self.clone()
+        })
+    }
+
+    fn rlp_fields(&self) -> Vec<RlpItem> {
+        vec![
+            self.nonce.into(),
+            self.gas_price.into(),
+            self.gas_limit.into(),
+            self.to
+                .map_or_else(|| RlpItem::Bytes(vec![]), RlpItem::from),
+            self.value.into(),
+            self.data.0.clone().into(),
+        ]
+    }
+}
+
+/// EIP-2718 type of an EIP-2930 transaction.
+const EIP2930_TX_TYPE: u8 = 0x01;
+
+/// An access list transaction (EIP-2930).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Eip2930Transaction {
+    pub chain_id: ChainId,
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas_limit: U256,
+    /// Recipient, `None` for contract creation.
+    pub to: Option<H160>,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: AccessList,
+}
+
+impl Eip2930Transaction {
+    /// Returns the hash that is signed, the chain id is part of the payload.
+    pub fn signing_hash(&self) -> H256 {
+        H256::from(keccak256(&typed(EIP2930_TX_TYPE, &self.rlp_fields())))
+    }
+
+    /// Signs the transaction with `sk`, returning the raw signed transaction
+    /// (type byte followed by the RLP payload) ready for
+    /// `send_raw_transaction`.
+    ///
+    /// Replay protection comes from the chain id in the payload, so unlike
+    /// EIP-155 the signature's `y_parity` is just the recovery id.
+    pub fn sign(&self, sk: &SecretKey) -> Bytes {
+        let (y_parity, r, s) = sign_hash(&self.signing_hash(), sk);
+
+        let mut items = self.rlp_fields();
+        items.extend_from_slice(&[y_parity.into(), r.into(), s.into()]);
+
+        Bytes(typed(EIP2930_TX_TYPE, &items))
+    }
+
+    /// Returns a copy of the transaction with the gas price raised by
+    /// `by_percent`, for replacing a stuck transaction. The nonce is
+    /// unchanged, sign the result with the original key.
+    pub fn bump_fee(&self, by_percent: u8) -> Result<Self, FeeBumpError> {
+        Ok(Eip2930Transaction {
+            gas_price: bump(self.gas_price, by_percent)?,
+            ..self.clone()
+        })
+    }
+
+    fn rlp_fields(&self) -> Vec<RlpItem> {
+        vec![
+            self.chain_id.as_u64().into(),
+            self.nonce.into(),
+            self.gas_price.into(),
+            self.gas_limit.into(),
+            self.to
+                .map_or_else(|| RlpItem::Bytes(vec![]), RlpItem::from),
+            self.value.into(),
+            self.data.0.clone().into(),
+            access_list_rlp(&self.access_list),
+        ]
+    }
+}
+
+/// EIP-2718 type of an EIP-1559 transaction.
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+/// A dynamic fee transaction (EIP-1559).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Eip1559Transaction {
+    pub chain_id: ChainId,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    /// Recipient, `None` for contract creation.
+    pub to: Option<H160>,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: AccessList,
+}
+
+impl Eip1559Transaction {
+    /// Returns the hash that is signed, the chain id is part of the payload.
+    pub fn signing_hash(&self) -> H256 {
+        H256::from(keccak256(&typed(EIP1559_TX_TYPE, &self.rlp_fields())))
+    }
+
+    /// Signs the transaction with `sk`, returning the raw signed transaction
+    /// (type byte followed by the RLP payload) ready for
+    /// `send_raw_transaction`.
+    pub fn sign(&self, sk: &SecretKey) -> Bytes {
+        let (y_parity, r, s) = sign_hash(&self.signing_hash(), sk);
+
+        let mut items = self.rlp_fields();
+        items.extend_from_slice(&[y_parity.into(), r.into(), s.into()]);
+
+        Bytes(typed(EIP1559_TX_TYPE, &items))
+    }
+
+    /// Returns a copy of the transaction with both the max fee and the
+    /// priority fee raised by `by_percent`, geth requires both to be bumped
+    /// to replace a stuck transaction.
+    pub fn bump_fee(&self, by_percent: u8) -> Result<Self, FeeBumpError> {
+        Ok(Eip1559Transaction {
+            max_priority_fee_per_gas: bump(self.max_priority_fee_per_gas, by_percent)?,
+            max_fee_per_gas: bump(self.max_fee_per_gas, by_percent)?,
+            ..self.clone()
+        })
+    }
+
+    fn rlp_fields(&self) -> Vec<RlpItem> {
+        vec![
+            self.chain_id.as_u64().into(),
+            self.nonce.into(),
+            self.max_priority_fee_per_gas.into(),
+            self.max_fee_per_gas.into(),
+            self.gas_limit.into(),
+            self.to
+                .map_or_else(|| RlpItem::Bytes(vec![]), RlpItem::from),
+            self.value.into(),
+            self.data.0.clone().into(),
+            access_list_rlp(&self.access_list),
+        ]
+    }
+}
+
+/// Returns the data of a contract creation transaction: the contract's init
+/// `bytecode` followed by the ABI encoded `constructor_args`.
+pub fn deploy_contract_data(bytecode: Vec<u8>, constructor_args: &[ethabi::Token]) -> Vec<u8> {
+    let mut data = bytecode;
+    data.extend(ethabi::encode(constructor_args));
+    data
+}
+
+/// Returns the address of the contract created by `sender` in a transaction
+/// with `nonce`, the low 20 bytes of `keccak256(rlp([sender, nonce]))`.
+pub fn contract_address(sender: H160, nonce: u64) -> H160 {
+    let hash = keccak256(&rlp::encode(&[sender.into(), nonce.into()]));
+    H160::from_slice(&hash[12..])
+}
+
+/// Raises `price` by `by_percent`, rounding up so the result always meets
+/// the minimum bump.
+fn bump(price: U256, by_percent: u8) -> Result<U256, FeeBumpError> {
+    if by_percent < MIN_FEE_BUMP_PERCENT {
+        return Err(FeeBumpError::TooSmall(by_percent));
+    }
+    let bumped = price
+        .checked_mul(U256::from(100 + u32::from(by_percent)))
+        .and_then(|bumped| bumped.checked_add(U256::from(99)))
+        .ok_or(FeeBumpError::Overflow)?;
+
+    Ok(bumped / 100)
+}
+
+fn access_list_rlp(access_list: &AccessList) -> RlpItem {
+    access_list
+        .iter()
+        .map(|item| {
+            let keys = item.storage_keys.iter().map(|key| RlpItem::from(*key));
+            RlpItem::List(vec![item.address.into(), RlpItem::List(keys.collect())])
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Encodes an EIP-2718 typed transaction payload.
+fn typed(tx_type: u8, items: &[RlpItem]) -> Vec<u8> {
+    let mut out = vec![tx_type];
+    out.extend(rlp::encode(items));
+    out
+}
+
+/// Signs `hash` with `sk`, returning the recovery id and the `r` and `s`
+/// values of the signature.
+fn sign_hash(hash: &H256, sk: &SecretKey) -> (u64, U256, U256) {
+    let secp = Secp256k1::signing_only();
+    let message = Message::from_slice(hash.as_bytes()).expect("hash is 32 bytes");
+
+    let (recovery_id, signature) = secp.sign_recoverable(&message, sk).serialize_compact();
+    let recovery_id = u64::try_from(recovery_id.to_i32()).expect("recovery id is 0 to 3");
+
+    (
+        recovery_id,
+        U256::from_big_endian(&signature[..32]),
+        U256::from_big_endian(&signature[32..]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+    use secp256k1::PublicKey;
+
+    use super::*;
+    use crate::secret_key_from_hex;
+    use crate::types::AccessListItem;
+
+    // Example from EIP-155.
+    fn eip155_example() -> LegacyTransaction {
+        LegacyTransaction {
+            nonce: 9.into(),
+            gas_price: U256::from(20) * U256::exp10(9),
+            gas_limit: 21_000.into(),
+            to: Some(H160::repeat_byte(0x35)),
+            value: U256::exp10(18),
+            data: Bytes::default(),
+        }
+    }
+
+    fn eip155_key() -> SecretKey {
+        secret_key_from_hex(&"46".repeat(32)).unwrap()
+    }
+
+    #[test]
+    fn can_parse_chain_id() {
+        assert_eq!("Mainnet".parse(), Ok(ChainId::MAINNET));
+        assert_eq!("5".parse(), Ok(ChainId::GOERLI));
+        assert_eq!("goerli".parse(), Ok(ChainId::GOERLI));
+        assert_eq!("SEPOLIA".parse(), Ok(ChainId::SEPOLIA));
+        assert_eq!("1337".parse(), Ok(ChainId::new(1337)));
+        assert_eq!(
+            "narnia".parse::<ChainId>(),
+            Err(UnknownChain("narnia".to_owned()))
+        );
+    }
+
+    #[test]
+    fn chain_id_serializes_as_integer() {
+        assert_eq!(serde_json::to_string(&ChainId::MAINNET).unwrap(), "1");
+        assert_eq!(
+            serde_json::from_str::<ChainId>("1").unwrap(),
+            ChainId::MAINNET
+        );
+
+        let json = serde_json::to_string(&ChainId::SEPOLIA).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ChainId>(&json).unwrap(),
+            ChainId::SEPOLIA
+        );
+    }
+
+    #[test]
+    fn chain_id_deserializes_from_string() {
+        assert_eq!(
+            serde_json::from_str::<ChainId>(r#""1""#).unwrap(),
+            ChainId::MAINNET
+        );
+        assert_eq!(
+            serde_json::from_str::<ChainId>(r#""0x5""#).unwrap(),
+            ChainId::GOERLI
+        );
+        assert!(serde_json::from_str::<ChainId>(r#""narnia""#).is_err());
+        assert!(serde_json::from_str::<ChainId>("-1").is_err());
+    }
+
+    #[test]
+    fn eip155_signing_hash() {
+        let hash = eip155_example().signing_hash(ChainId::MAINNET);
+
+        assert_eq!(
+            hex::encode(hash),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+    }
+
+    #[test]
+    fn eip155_signed_transaction() {
+        let raw = eip155_example().sign(&eip155_key(), ChainId::MAINNET);
+
+        assert_eq!(
+            hex::encode(raw.0),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+    }
+
+    #[test]
+    fn chain_id_round_trips_through_v() {
+        let raw = eip155_example().sign(&eip155_key(), ChainId::ROPSTEN);
+
+        let decoded = rlp::decode(&raw.0).unwrap();
+        let fields = decoded.as_list().unwrap();
+        assert_eq!(fields.len(), 9);
+
+        let v = U256::from_big_endian(fields[6].as_bytes().unwrap()).as_u64();
+        assert!(v == 41 || v == 42, "v: {}", v);
+        assert_eq!((v - 35) / 2, ChainId::ROPSTEN.as_u64());
+    }
+
+    #[test]
+    fn huge_chain_id_does_not_overflow_v() {
+        let chain_id = ChainId::new(u64::MAX);
+        let raw = eip155_example().sign(&eip155_key(), chain_id);
+
+        let decoded = rlp::decode(&raw.0).unwrap();
+        let v = U256::from_big_endian(decoded.as_list().unwrap()[6].as_bytes().unwrap());
+        let min = U256::from(u64::MAX) * U256::from(2) + U256::from(35);
+        assert!(v == min || v == min + 1, "v: {}", v);
+    }
+
+    fn eip2930_example() -> Eip2930Transaction {
+        Eip2930Transaction {
+            chain_id: ChainId::MAINNET,
+            nonce: 1.into(),
+            gas_price: U256::from(20) * U256::exp10(9),
+            gas_limit: 30_000.into(),
+            to: Some(H160::repeat_byte(0x35)),
+            value: U256::exp10(18),
+            data: Bytes::default(),
+            access_list: vec![AccessListItem {
+                address: H160::repeat_byte(0x11),
+                storage_keys: vec![H256::zero(), H256::from_low_u64_be(1)],
+            }],
+        }
+    }
+
+    /// `emptyEip2718Tx` from go-ethereum's `core/types/transaction_test.go`.
+    fn geth_eip2930_example() -> Eip2930Transaction {
+        Eip2930Transaction {
+            chain_id: ChainId::MAINNET,
+            nonce: 3.into(),
+            gas_price: 1.into(),
+            gas_limit: 25_000.into(),
+            to: Some("b94f5374fce5edbc8e2a8697c15331677e6ebf0b".parse().unwrap()),
+            value: 10.into(),
+            data: Bytes(hex::decode("5544").unwrap()),
+            access_list: vec![],
+        }
+    }
+
+    #[test]
+    fn eip2930_signing_hash() {
+        // TestEIP2718TransactionSigHash in go-ethereum.
+        let hash = geth_eip2930_example().signing_hash();
+
+        assert_eq!(
+            hex::encode(hash),
+            "49b486f0ec0a60dfbbca2d30cb07c9e8ffb2a2ff41f29a1ab6737475f6ff69f3"
+        );
+    }
+
+    #[test]
+    fn eip2930_encoding() {
+        // The binary representation in TestEIP2718TransactionEncode in
+        // go-ethereum, signed with `signedEip2718Tx`'s signature.
+        let r = U256::from_str("c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660")
+            .unwrap();
+        let s = U256::from_str("32f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521")
+            .unwrap();
+
+        let mut items = geth_eip2930_example().rlp_fields();
+        items.extend_from_slice(&[1u64.into(), r.into(), s.into()]);
+
+        assert_eq!(
+            hex::encode(typed(EIP2930_TX_TYPE, &items)),
+            "01f8630103018261a894b94f5374fce5edbc8e2a8697c15331677e6ebf0b0a825544c001a0c9519f4f2b\
+             30335884581971573fadf60c6204f59a911df35ee8a540456b2660a032f1e8e2c5dd761f9e4f88f41c83\
+             10aeaba26a8bfcdacfedfa12ec3862d37521"
+        );
+    }
+
+    // The signed vectors below use the EIP-155 example key. The expected
+    // bytes come from an independent implementation (RFC 6979 nonces, low
+    // `s`) that also reproduces the EIP-155 example and the geth hash above.
+
+    #[test]
+    fn eip2930_signed_with_known_key() {
+        let raw = geth_eip2930_example().sign(&eip155_key());
+
+        assert_eq!(
+            raw.to_hex(),
+            "0x01f8630103018261a894b94f5374fce5edbc8e2a8697c15331677e6ebf0b0a825544c080a0cd2d12da\
+             64aafdb53890836687c0b3aab1711c659ecd3928313464126d6c23f4a047ac9de50caa91049fe6956e00\
+             82f78b934facba2dc10a0853b0b69503952d5e"
+        );
+        assert_eq!(
+            hex::encode(keccak256(&raw.0)),
+            "7643850b1f03988b6bdd2a33ff362c0808e2806ae7f88d01803f9c88659d48e0"
+        );
+    }
+
+    #[test]
+    fn eip1559_signed_with_known_key() {
+        let tx = Eip1559Transaction {
+            chain_id: ChainId::MAINNET,
+            nonce: 3.into(),
+            max_priority_fee_per_gas: U256::from(2) * U256::exp10(9),
+            max_fee_per_gas: U256::from(100) * U256::exp10(9),
+            gas_limit: 21_000.into(),
+            to: Some(H160::repeat_byte(0x35)),
+            value: U256::exp10(18),
+            data: Bytes::default(),
+            access_list: vec![],
+        };
+
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "69f677afff6b87080067ab5e91dce4cf1cc0dafe0429095310b8322468b4205e"
+        );
+        let raw = tx.sign(&eip155_key());
+        assert_eq!(
+            raw.to_hex(),
+            "0x02f8730103847735940085174876e800825208943535353535353535353535353535353535353535880d\
+             e0b6b3a764000080c001a005c2578f640b1bf88dda688c119f5c7a0b5804753ccef7cf74ecf4e9713b8a\
+             e8a0431484d707487a192e707ccae4ecc4e10561411c6afd37e82b90f95575a0ca49"
+        );
+        assert_eq!(
+            hex::encode(keccak256(&raw.0)),
+            "9c1da7567d1c1cf9bd8fe2d3ece01ffd567d2f2d2997301a87711bb096db923e"
+        );
+    }
+
+    #[test]
+    fn eip2930_signed_transaction_recovers_signer() {
+        let sk = eip155_key();
+        let tx = eip2930_example();
+        let raw = tx.sign(&sk);
+
+        assert_eq!(raw.0[0], EIP2930_TX_TYPE);
+        let decoded = rlp::decode(&raw.0[1..]).unwrap();
+        let fields = decoded.as_list().unwrap();
+        assert_eq!(fields.len(), 11);
+        assert_eq!(
+            hex::encode(fields[7].encode()),
+            "f85bf859941111111111111111111111111111111111111111f842a000000000000000000000000000\
+             00000000000000000000000000000000000000a0000000000000000000000000000000000000000000\
+             0000000000000000000001"
+        );
+
+        let y_parity = fields[8].as_bytes().unwrap().first().copied().unwrap_or(0);
+        let mut compact = [0u8; 64];
+        let (r, s) = (
+            fields[9].as_bytes().unwrap(),
+            fields[10].as_bytes().unwrap(),
+        );
+        compact[32 - r.len()..32].copy_from_slice(r);
+        compact[64 - s.len()..].copy_from_slice(s);
+
+        let recovery_id = RecoveryId::from_i32(i32::from(y_parity)).unwrap();
+        let signature = RecoverableSignature::from_compact(&compact, recovery_id).unwrap();
+        let message = Message::from_slice(tx.signing_hash().as_bytes()).unwrap();
+        let recovered = Secp256k1::verification_only()
+            .recover(&message, &signature)
+            .unwrap();
+
+        assert_eq!(
+            recovered,
+            PublicKey::from_secret_key(&Secp256k1::signing_only(), &sk)
+        );
+    }
+
+    #[test]
+    fn contract_address_matches_create() {
+        let sender: H160 = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            contract_address(sender, 0),
+            "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            contract_address(sender, 1),
+            "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn deploy_data_appends_constructor_args() {
+        let data = deploy_contract_data(vec![0x60, 0x80], &[ethabi::Token::Uint(1.into())]);
+
+        assert_eq!(data.len(), 2 + 32);
+        assert_eq!(data[..2], [0x60, 0x80]);
+        assert_eq!(data[33], 1);
+    }
+
+    #[test]
+    fn bump_fee_raises_gas_price_and_keeps_nonce() {
+        let tx = eip155_example();
+        let bumped = tx.bump_fee(MIN_FEE_BUMP_PERCENT).unwrap();
+
+        assert_eq!(bumped.nonce, tx.nonce);
+        assert_eq!(bumped.gas_price, U256::from(22) * U256::exp10(9));
+        assert_eq!(bumped, LegacyTransaction {
+            gas_price: bumped.gas_price,
+            ..tx
+        });
+    }
+
+    #[test]
+    fn bump_fee_rounds_up() {
+        let tx = LegacyTransaction {
+            gas_price: 15.into(),
+            ..eip155_example()
+        };
+
+        // 15 * 1.1 = 16.5, geth requires at least that.
+        assert_eq!(tx.bump_fee(10).unwrap().gas_price, 17.into());
+    }
+
+    #[test]
+    fn bump_fee_enforces_minimum() {
+        assert_eq!(eip155_example().bump_fee(9), Err(FeeBumpError::TooSmall(9)));
+        assert_eq!(
+            eip2930_example().bump_fee(0),
+            Err(FeeBumpError::TooSmall(0))
+        );
+    }
+
+    #[test]
+    fn bump_fee_reports_overflow() {
+        let tx = LegacyTransaction {
+            gas_price: U256::MAX,
+            ..eip155_example()
+        };
+        assert_eq!(tx.bump_fee(10), Err(FeeBumpError::Overflow));
+    }
+
+    #[test]
+    fn eip1559_bump_fee_raises_both_fees() {
+        let tx = Eip1559Transaction {
+            max_priority_fee_per_gas: U256::exp10(9),
+            max_fee_per_gas: U256::from(30) * U256::exp10(9),
+            ..Default::default()
+        };
+        let bumped = tx.bump_fee(10).unwrap();
+
+        assert_eq!(
+            bumped.max_priority_fee_per_gas,
+            U256::from(11) * U256::exp10(8)
+        );
+        assert_eq!(bumped.max_fee_per_gas, U256::from(33) * U256::exp10(9));
+    }
+
+    #[test]
+    fn eip1559_signed_transaction_is_typed() {
+        let raw = Eip1559Transaction {
+            chain_id: ChainId::MAINNET,
+            ..Default::default()
+        }
+        .sign(&eip155_key());
+
+        assert_eq!(raw.0[0], EIP1559_TX_TYPE);
+        let decoded = rlp::decode(&raw.0[1..]).unwrap();
+        assert_eq!(decoded.as_list().unwrap().len(), 12);
+    }
+
+    #[test]
+    fn eip2930_bump_fee_keeps_nonce() {
+        let tx = eip2930_example();
+        let bumped = tx.bump_fee(25).unwrap();
+
+        assert_eq!(bumped.nonce, tx.nonce);
+        assert_eq!(bumped.gas_price, U256::from(25) * U256::exp10(9));
+    }
+}
@@ -1,23 +1,142 @@
 //! Etherum types copied from `rust-web3`
 //! ref: https://github.com/tomusdrw/rust-web3
 
+use thiserror::Error;
+
+mod access_list;
+mod address;
 mod block;
 mod bytes;
+mod erc20;
+mod fee_history;
 mod log;
+mod proof;
+mod simulation;
+mod state_override;
+mod trace;
 mod transaction;
 mod transaction_request;
+mod txpool;
 mod uint;
 
 pub use self::{
-    block::{Block, BlockHeader, BlockId, BlockNumber},
+    access_list::{AccessList, AccessListItem},
+    address::{parse_checksum_address, to_checksum_string, ChecksumAddress, ParseAddressError},
+    block::{Block, BlockHeader, BlockId, BlockNumber, Withdrawal},
     bytes::Bytes,
-    log::{Filter, FilterBuilder, Log},
-    transaction::{RawTransaction, Receipt as TransactionReceipt, Transaction},
-    transaction_request::{CallRequest, TransactionCondition, TransactionRequest},
-    uint::{H128, H160, H2048, H256, H512, H520, H64, U128, U256, U64},
+    erc20::{Erc20Metadata, Erc20Transfer},
+    fee_history::FeeHistory,
+    log::{
+        event_topic, Event, Filter, FilterBuilder, FilterId, Log, LogDeduplicator, LogRouter,
+        TopicIndex,
+    },
+    proof::{AccountProof, StorageProof},
+    simulation::SimulationResult,
+    state_override::{AccountOverride, StateOverride},
+    trace::CallTrace,
+    transaction::{RawTransaction, Receipt as TransactionReceipt, RecoverSenderError, Transaction},
+    transaction_request::{CallRequest, TransactionCondition, TransactionRequest, TxParams},
+    txpool::{TxpoolContent, TxpoolStatus},
+    uint::{
+        parse_quantity, BigEndianHash, ParseQuantityError, H128, H160, H2048, H256, H512, H520,
+        H64, U128, U256, U64,
+    },
 };
 
 /// Address
 pub type Address = H160;
 /// Index in block
 pub type Index = U64;
+
+/// Error returned when parsing a fixed length hex string, e.g. an address.
+#[derive(Clone, Copy, Debug, Error, PartialEq)]
+pub enum ParseHexError {
+    #[error("expected {expected} hex characters, got {got}")]
+    Length { expected: usize, got: usize },
+    #[error("not valid hex")]
+    Hex(#[source] hex::FromHexError),
+}
+
+/// Parses an address from a hex string, the `0x` prefix is optional and
+/// hex digits are case insensitive (the checksum is not verified).
+pub fn parse_address(s: &str) -> Result<Address, ParseHexError> {
+    let bytes = parse_fixed_hex(s, 20)?;
+    Ok(Address::from_slice(&bytes))
+}
+
+/// Parses a 32 byte hash from a hex string, the `0x` prefix is optional and
+/// hex digits are case insensitive.
+pub fn parse_h256(s: &str) -> Result<H256, ParseHexError> {
+    let bytes = parse_fixed_hex(s, 32)?;
+    Ok(H256::from_slice(&bytes))
+}
+
+/// Decodes `s` as hex, requiring exactly `len` bytes.
+fn parse_fixed_hex(s: &str, len: usize) -> Result<Vec<u8>, ParseHexError> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() != len * 2 {
+        return Err(ParseHexError::Length {
+            expected: len * 2,
+            got: s.len(),
+        });
+    }
+
+    hex::decode(s).map_err(ParseHexError::Hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0x0EB44ea45B049fc225Cfdf07883dD89C7FeBd8f0";
+    const HASH: &str = "0x422fb0d5953c0c48cbb42fb58e1c30f5e150441c68374d70ca7d4f191fd56f26";
+
+    #[test]
+    fn can_parse_address() {
+        let want: Address = ADDRESS.parse().unwrap();
+
+        assert_eq!(parse_address(ADDRESS).unwrap(), want);
+        assert_eq!(parse_address(&ADDRESS[2..]).unwrap(), want);
+        assert_eq!(parse_address(&ADDRESS.to_lowercase()).unwrap(), want);
+        assert_eq!(parse_address(&ADDRESS.to_uppercase()[2..]).unwrap(), want);
+    }
+
+    #[test]
+    fn can_parse_h256() {
+        let want: H256 = HASH.parse().unwrap();
+
+        assert_eq!(parse_h256(HASH).unwrap(), want);
+        assert_eq!(parse_h256(&HASH[2..]).unwrap(), want);
+    }
+
+    #[test]
+    fn rejects_too_short_input() {
+        let err = parse_address(&ADDRESS[..40]).unwrap_err();
+        assert_eq!(err, ParseHexError::Length {
+            expected: 40,
+            got: 38
+        });
+        assert_eq!(err.to_string(), "expected 40 hex characters, got 38");
+
+        assert!(parse_h256(&HASH[..64]).is_err());
+    }
+
+    #[test]
+    fn rejects_too_long_input() {
+        let err = parse_address(&format!("{}00", ADDRESS)).unwrap_err();
+        assert_eq!(err, ParseHexError::Length {
+            expected: 40,
+            got: 42
+        });
+
+        assert!(parse_h256(&format!("{}00", HASH)).is_err());
+    }
+
+    #[test]
+    fn rejects_non_hex_input() {
+        let err = parse_address(&ADDRESS.replace('E', "g")).unwrap_err();
+        assert!(matches!(err, ParseHexError::Hex(_)), "{:?}", err);
+
+        assert!(parse_h256(&HASH.replace('f', "z")).is_err());
+    }
+}
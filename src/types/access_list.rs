@@ -0,0 +1,39 @@
+use crate::types::{H160, H256};
+use serde::{Deserialize, Serialize};
+
+/// List of addresses and storage keys a transaction plans to access
+/// (EIP-2930).
+pub type AccessList = Vec<AccessListItem>;
+
+/// An address and the storage keys accessed at that address.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AccessListItem {
+    /// Accessed address
+    pub address: H160,
+    /// Accessed storage keys
+    #[serde(rename = "storageKeys")]
+    pub storage_keys: Vec<H256>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_serialize_access_list() {
+        let list: AccessList = vec![AccessListItem {
+            address: H160::repeat_byte(0x11),
+            storage_keys: vec![H256::zero()],
+        }];
+
+        let json = serde_json::to_value(&list).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "address": "0x1111111111111111111111111111111111111111",
+                "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000000"]
+            }])
+        );
+        assert_eq!(serde_json::from_value::<AccessList>(json).unwrap(), list);
+    }
+}
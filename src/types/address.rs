@@ -0,0 +1,153 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use super::{parse_address, Address, ParseHexError};
+use crate::keccak256;
+
+/// An address that displays in full, `0x` prefixed, with the EIP-55 mixed
+/// case checksum (`H160` displays truncated as `0x5aae…aed`).
+///
+/// Parsing accepts all lowercase or all uppercase hex, mixed case input must
+/// have a valid checksum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChecksumAddress(pub Address);
+
+impl fmt::Display for ChecksumAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_checksum_string(&self.0))
+    }
+}
+
+/// Error returned when parsing a `ChecksumAddress`.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ParseAddressError {
+    #[error("invalid address")]
+    Hex(#[from] ParseHexError),
+    #[error("invalid address checksum: {0}")]
+    Checksum(String),
+}
+
+impl FromStr for ChecksumAddress {
+    type Err = ParseAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = parse_address(s)?;
+
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        let is_mixed_case = hex.chars().any(|c| c.is_ascii_lowercase())
+            && hex.chars().any(|c| c.is_ascii_uppercase());
+        if is_mixed_case && hex != &to_checksum_string(&address)[2..] {
+            return Err(ParseAddressError::Checksum(s.to_owned()));
+        }
+
+        Ok(ChecksumAddress(address))
+    }
+}
+
+impl From<Address> for ChecksumAddress {
+    fn from(address: Address) -> Self {
+        ChecksumAddress(address)
+    }
+}
+
+impl From<ChecksumAddress> for Address {
+    fn from(address: ChecksumAddress) -> Self {
+        address.0
+    }
+}
+
+/// Returns `address` as a `0x` prefixed EIP-55 checksummed hex string.
+///
+/// ref: https://eips.ethereum.org/EIPS/eip-55
+pub fn to_checksum_string(address: &Address) -> String {
+    let hex = hex::encode(address.as_bytes());
+    let hash = keccak256(hex.as_bytes());
+
+    let mut s = String::with_capacity(42);
+    s.push_str("0x");
+    for (i, c) in hex.chars().enumerate() {
+        // Uppercase letters whose corresponding hash nibble is >= 8.
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+        if nibble >= 8 {
+            s.push(c.to_ascii_uppercase());
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+/// Parses a checksummed address, see `ChecksumAddress`.
+pub fn parse_checksum_address(s: &str) -> Result<Address, ParseAddressError> {
+    let address: ChecksumAddress = s.parse()?;
+    Ok(address.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from EIP-55.
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn displays_eip55_checksum() {
+        for s in CHECKSUMMED.iter() {
+            let address = parse_address(s).unwrap();
+            assert_eq!(ChecksumAddress(address).to_string(), *s);
+            assert_eq!(to_checksum_string(&address), *s);
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for s in CHECKSUMMED.iter() {
+            let address: ChecksumAddress = s.parse().unwrap();
+            assert_eq!(
+                address.to_string().parse::<ChecksumAddress>().unwrap(),
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_single_case_input() {
+        let want: ChecksumAddress = CHECKSUMMED[0].parse().unwrap();
+
+        assert_eq!(
+            CHECKSUMMED[0]
+                .to_lowercase()
+                .parse::<ChecksumAddress>()
+                .unwrap(),
+            want
+        );
+        assert_eq!(
+            CHECKSUMMED[0][2..]
+                .to_uppercase()
+                .parse::<ChecksumAddress>()
+                .unwrap(),
+            want
+        );
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        // Flip the case of the first letter.
+        let bad = CHECKSUMMED[0].replacen('a', "A", 1);
+        let err = bad.parse::<ChecksumAddress>().unwrap_err();
+
+        assert_eq!(err, ParseAddressError::Checksum(bad.clone()));
+        assert!(parse_checksum_address(&bad).is_err());
+    }
+}
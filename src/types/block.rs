@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use crate::types::{Bytes, H160, H2048, H256, H64, U256, U64};
 use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 
@@ -48,6 +51,9 @@ pub struct BlockHeader {
     pub mix_hash: Option<H256>,
     /// Nonce
     pub nonce: Option<H64>,
+    /// Fields not modelled above, e.g. ones added by later forks
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// The block type returned from RPC calls.
@@ -110,6 +116,40 @@ pub struct Block<TX> {
     pub mix_hash: Option<H256>,
     /// Nonce
     pub nonce: Option<H64>,
+    /// Withdrawals from the beacon chain, None before Shanghai
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Fields not modelled above, e.g. ones added by later forks
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl<TX> Block<TX> {
+    /// Returns the withdrawals in the block, empty before Shanghai.
+    pub fn withdrawals(&self) -> &[Withdrawal] {
+        self.withdrawals.as_deref().unwrap_or_default()
+    }
+}
+
+/// A validator withdrawal from the beacon chain (EIP-4895).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Withdrawal {
+    /// Index of the withdrawal, increments across blocks
+    pub index: U64,
+    /// Index of the validator
+    #[serde(rename = "validatorIndex")]
+    pub validator_index: U64,
+    /// Recipient address
+    pub address: H160,
+    /// Amount in Gwei, not wei
+    pub amount: U64,
+}
+
+impl Withdrawal {
+    /// Returns the amount in wei.
+    pub fn amount_wei(&self) -> U256 {
+        U256::from(self.amount.as_u64()) * U256::exp10(9)
+    }
 }
 
 /// Block Number
@@ -121,6 +161,11 @@ pub enum BlockNumber {
     Earliest,
     /// Pending block (not yet part of the blockchain)
     Pending,
+    /// Most recent block finalized by the consensus layer (post-Merge)
+    Finalized,
+    /// Most recent block considered safe from reorgs by the consensus layer
+    /// (post-Merge)
+    Safe,
     /// Block by number from canon chain
     Number(U64),
 }
@@ -131,17 +176,25 @@ impl<T: Into<U64>> From<T> for BlockNumber {
     }
 }
 
+impl fmt::Display for BlockNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            BlockNumber::Number(ref x) => write!(f, "0x{:x}", x),
+            BlockNumber::Latest => f.write_str("latest"),
+            BlockNumber::Earliest => f.write_str("earliest"),
+            BlockNumber::Pending => f.write_str("pending"),
+            BlockNumber::Finalized => f.write_str("finalized"),
+            BlockNumber::Safe => f.write_str("safe"),
+        }
+    }
+}
+
 impl Serialize for BlockNumber {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        match *self {
-            BlockNumber::Number(ref x) => serializer.serialize_str(&format!("0x{:x}", x)),
-            BlockNumber::Latest => serializer.serialize_str("latest"),
-            BlockNumber::Earliest => serializer.serialize_str("earliest"),
-            BlockNumber::Pending => serializer.serialize_str("pending"),
-        }
+        serializer.collect_str(self)
     }
 }
 
@@ -221,8 +274,119 @@ mod tests {
             "uncles": []
           }"#;
 
-        let block: Block<()> = serde_json::from_str(&EXAMPLE_BLOCK).unwrap();
+        let block: Block<()> = serde_json::from_str(EXAMPLE_BLOCK).unwrap();
 
         assert_eq!(block.author, Default::default());
     }
+
+    const CANCUN_BLOCK: &str = r#"{
+        "baseFeePerGas": "0x1bb5e3e1e",
+        "blobGasUsed": "0x20000",
+        "difficulty": "0x0",
+        "excessBlobGas": "0x0",
+        "extraData": "0x6265617665726275696c642e6f7267",
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x1238bd0",
+        "hash": "0x9cd3bb6dcbbf10ac1ba1ac2f12b8d1b0c3ae77f1462f4f0ef1c5b77c4ff85ae0",
+        "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+        "mixHash": "0x6d3dc9ea4c5e0f2e4e9b8f5e0a6b8f0c1c5f0d7e1a2b3c4d5e6f708192a3b4c5",
+        "nonce": "0x0000000000000000",
+        "number": "0x12a05f2",
+        "parentBeaconBlockRoot": "0x0b8a6b7e1f3f0a3d4c5b6a79881726354f4e3d2c1b0a99887766554433221100",
+        "parentHash": "0x2cbd9fa6ed9cc3ab3f5e2f6e5d1a0f7c0f6e5d4c3b2a1908f7e6d5c4b3a29180",
+        "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        "size": "0x1a2b",
+        "stateRoot": "0xd5855eb08b3387c0af375e9cdb6acfc05eb8f519e419b874b6ff2ffda7ed1dff",
+        "timestamp": "0x65f5f36b",
+        "totalDifficulty": "0xc70d815d562d3cfa955",
+        "transactions": [],
+        "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "uncles": [],
+        "withdrawals": [
+            {
+                "index": "0x2a2e0c4",
+                "validatorIndex": "0xf3b5a",
+                "address": "0xb9d7934878b5fb9610b3fe8a5e441e8fad7e293f",
+                "amount": "0x11b1f3f"
+            }
+        ],
+        "withdrawalsRoot": "0x2a2e3c6d5e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f70819"
+    }"#;
+
+    #[test]
+    fn unknown_block_fields_are_kept() {
+        let block: Block<H256> = serde_json::from_str(CANCUN_BLOCK).unwrap();
+
+        assert_eq!(block.number, Some(0x12a05f2.into()));
+        assert_eq!(block.extra["blobGasUsed"], "0x20000");
+        assert_eq!(block.extra["excessBlobGas"], "0x0");
+        // Modelled fields are not duplicated.
+        assert!(!block.extra.contains_key("number"));
+
+        let header: BlockHeader = serde_json::from_str(CANCUN_BLOCK).unwrap();
+        assert_eq!(header.extra["blobGasUsed"], "0x20000");
+        // A header does not model the block body, so it is kept verbatim.
+        assert_eq!(header.extra["transactions"], serde_json::json!([]));
+        assert!(!block.extra.contains_key("transactions"));
+    }
+
+    #[test]
+    fn can_deserialize_withdrawals() {
+        let block: Block<H256> = serde_json::from_str(CANCUN_BLOCK).unwrap();
+
+        assert_eq!(block.withdrawals(), &[Withdrawal {
+            index: 0x2a2e0c4.into(),
+            validator_index: 0xf3b5a.into(),
+            address: "0xb9d7934878b5fb9610b3fe8a5e441e8fad7e293f"
+                .parse()
+                .unwrap(),
+            amount: 0x11b1f3f.into(),
+        }]);
+        assert!(!block.extra.contains_key("withdrawals"));
+
+        // 0x11b1f3f Gwei is 0.018554687 ether.
+        assert_eq!(
+            block.withdrawals()[0].amount_wei(),
+            U256::from(18_554_687u64) * U256::exp10(9)
+        );
+    }
+
+    #[test]
+    fn pre_shanghai_block_has_no_withdrawals() {
+        let mut json: serde_json::Value = serde_json::from_str(CANCUN_BLOCK).unwrap();
+        json.as_object_mut().unwrap().remove("withdrawals");
+
+        let block: Block<H256> = serde_json::from_value(json).unwrap();
+        assert!(block.withdrawals().is_empty());
+    }
+
+    #[test]
+    fn unknown_block_fields_round_trip() {
+        let block: Block<H256> = serde_json::from_str(CANCUN_BLOCK).unwrap();
+
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["blobGasUsed"], "0x20000");
+        assert_eq!(serde_json::from_value::<Block<H256>>(json).unwrap(), block);
+    }
+
+    #[test]
+    fn block_number_display() {
+        assert_eq!(BlockNumber::Latest.to_string(), "latest");
+        assert_eq!(BlockNumber::Earliest.to_string(), "earliest");
+        assert_eq!(BlockNumber::Pending.to_string(), "pending");
+        assert_eq!(BlockNumber::Finalized.to_string(), "finalized");
+        assert_eq!(BlockNumber::Safe.to_string(), "safe");
+        assert_eq!(BlockNumber::from(255).to_string(), "0xff");
+    }
+
+    #[test]
+    fn block_number_serialize() {
+        let ser = |n: BlockNumber| serde_json::to_string(&n).unwrap();
+
+        assert_eq!(ser(BlockNumber::Finalized), r#""finalized""#);
+        assert_eq!(ser(BlockNumber::Safe), r#""safe""#);
+        assert_eq!(ser(BlockNumber::from(16)), r#""0x10""#);
+    }
 }
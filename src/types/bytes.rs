@@ -9,6 +9,20 @@ use serde::{
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Bytes(pub Vec<u8>);
 
+impl Bytes {
+    /// Decodes bytes from a hex string, the `0x` prefix is optional.
+    pub fn from_hex(s: &str) -> Result<Self, hex::FromHexError> {
+        let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s))?;
+        Ok(Bytes(bytes))
+    }
+
+    /// Encodes the bytes as `0x` prefixed lowercase hex, empty bytes are
+    /// encoded as `0x`.
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(&self.0))
+    }
+}
+
 impl<T: Into<Vec<u8>>> From<T> for Bytes {
     fn from(data: T) -> Self {
         Bytes(data.into())
@@ -20,9 +34,7 @@ impl Serialize for Bytes {
     where
         S: Serializer,
     {
-        let mut serialized = "0x".to_owned();
-        serialized.push_str(&hex::encode(&self.0));
-        serializer.serialize_str(serialized.as_ref())
+        serializer.serialize_str(&self.to_hex())
     }
 }
 
@@ -48,10 +60,8 @@ impl<'a> Visitor<'a> for BytesVisitor {
     where
         E: Error,
     {
-        if value.len() >= 2 && &value[0..2] == "0x" {
-            let bytes = hex::decode(&value[2..])
-                .map_err(|e| Error::custom(format!("Invalid hex: {}", e)))?;
-            Ok(Bytes(bytes))
+        if value.starts_with("0x") {
+            Bytes::from_hex(value).map_err(|e| Error::custom(format!("Invalid hex: {}", e)))
         } else {
             Err(Error::invalid_value(Unexpected::Str(value), &"0x prefix"))
         }
@@ -64,3 +74,43 @@ impl<'a> Visitor<'a> for BytesVisitor {
         self.visit_str(value.as_ref())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_bytes() {
+        assert_eq!(Bytes::default().to_hex(), "0x");
+        assert_eq!(serde_json::to_string(&Bytes::default()).unwrap(), r#""0x""#);
+        assert_eq!(
+            serde_json::from_str::<Bytes>(r#""0x""#).unwrap(),
+            Bytes::default()
+        );
+        assert_eq!(Bytes::from_hex("").unwrap(), Bytes::default());
+    }
+
+    #[test]
+    fn rejects_odd_number_of_nibbles() {
+        assert!(Bytes::from_hex("0xabc").is_err());
+        assert!(serde_json::from_str::<Bytes>(r#""0xabc""#).is_err());
+    }
+
+    #[test]
+    fn rejects_missing_prefix_when_deserializing() {
+        assert!(serde_json::from_str::<Bytes>(r#""abcd""#).is_err());
+        assert_eq!(Bytes::from_hex("abcd").unwrap(), Bytes(vec![0xab, 0xcd]));
+    }
+
+    #[test]
+    fn call_data_round_trips() {
+        // balanceOf(0x0EB44ea45B049fc225Cfdf07883dD89C7FeBd8f0)
+        let hex = "0x70a082310000000000000000000000000eb44ea45b049fc225cfdf07883dd89c7febd8f0";
+        let bytes: Bytes = serde_json::from_str(&format!("\"{}\"", hex)).unwrap();
+
+        assert_eq!(&bytes.0[..4], &[0x70, 0xa0, 0x82, 0x31]);
+        assert_eq!(bytes.to_hex(), hex);
+        assert_eq!(serde_json::to_value(&bytes).unwrap(), hex);
+        assert_eq!(Bytes::from_hex(&hex.to_uppercase()[2..]).unwrap(), bytes);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::types::{Event, Log, H160, U256};
+
+/// Metadata of an ERC-20 token contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc20Metadata {
+    /// Name of the token, e.g. "USD Coin".
+    pub name: String,
+    /// Ticker symbol of the token, e.g. "USDC".
+    pub symbol: String,
+    /// Number of decimals used by the token's base unit.
+    pub decimals: u8,
+}
+
+/// An ERC-20 `Transfer(address indexed from, address indexed to, uint256
+/// value)` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Erc20Transfer {
+    /// The token contract that emitted the event.
+    pub token: H160,
+    pub from: H160,
+    pub to: H160,
+    /// Amount transferred, in the token's base unit.
+    pub value: U256,
+}
+
+impl Event for Erc20Transfer {
+    const SIGNATURE: &'static str = "Transfer(address,address,uint256)";
+
+    fn from_log(log: &Log) -> Option<Self> {
+        // ERC-721 `Transfer` has the same signature but indexes the token id,
+        // so has four topics and no data.
+        match log.topics() {
+            [topic, from, to] if *topic == Self::topic() && log.data().0.len() == 32 => {
+                Some(Erc20Transfer {
+                    token: log.address(),
+                    from: H160::from(*from),
+                    to: H160::from(*to),
+                    value: U256::from_big_endian(&log.data().0),
+                })
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::types::{U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Base fees and priority fee rewards of a range of blocks, returned by
+/// `eth_feeHistory`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FeeHistory {
+    /// Lowest block number of the range
+    #[serde(rename = "oldestBlock")]
+    pub oldest_block: U64,
+    /// Base fee per gas of each block, plus the next block after the range
+    #[serde(rename = "baseFeePerGas")]
+    pub base_fee_per_gas: Vec<U256>,
+    /// Ratio of gas used to gas limit of each block
+    #[serde(rename = "gasUsedRatio")]
+    pub gas_used_ratio: Vec<f64>,
+    /// Priority fee at each of the requested percentiles, per block
+    #[serde(default)]
+    pub reward: Option<Vec<Vec<U256>>>,
+}
@@ -1,3 +1,6 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
 use crate::types::{BlockNumber, Bytes, Index, H160, H256, U256, U64};
 use serde::{Deserialize, Serialize, Serializer};
 
@@ -48,6 +51,43 @@ impl Log {
         }
         false
     }
+
+    /// Returns the address of the contract that emitted the log.
+    pub fn address(&self) -> H160 {
+        self.address
+    }
+
+    /// Returns the topics of the log, for non-anonymous events the first is
+    /// the hash of the event signature.
+    pub fn topics(&self) -> &[H256] {
+        &self.topics
+    }
+
+    /// Returns the non-indexed data of the log.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Returns the hash of the block the log is in, `None` if pending.
+    pub fn block_hash(&self) -> Option<H256> {
+        self.block_hash
+    }
+
+    /// Returns the number of the block the log is in, `None` if pending.
+    pub fn block_number(&self) -> Option<u64> {
+        self.block_number.map(|n| n.as_u64())
+    }
+
+    /// Returns the hash of the transaction that emitted the log, `None` if
+    /// pending.
+    pub fn transaction_hash(&self) -> Option<H256> {
+        self.transaction_hash
+    }
+
+    /// Returns true if the log was removed by a reorg, same as `is_removed`.
+    pub fn removed(&self) -> bool {
+        self.is_removed()
+    }
 }
 
 #[derive(Default, Debug, PartialEq, Clone)]
@@ -92,6 +132,54 @@ pub struct Filter {
     limit: Option<usize>,
 }
 
+impl Filter {
+    /// Returns the first block of the range, if set.
+    pub fn from_block(&self) -> Option<BlockNumber> {
+        self.from_block
+    }
+
+    /// Returns the last block of the range, if set.
+    pub fn to_block(&self) -> Option<BlockNumber> {
+        self.to_block
+    }
+
+    /// Returns the block hash, if set.
+    pub fn block_hash(&self) -> Option<H256> {
+        self.block_hash
+    }
+
+    /// Returns a copy of the filter for the blocks `from..=to`.
+    pub(crate) fn with_block_range(&self, from: u64, to: u64) -> Filter {
+        Filter {
+            from_block: Some(from.into()),
+            to_block: Some(to.into()),
+            block_hash: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Position of a topic in a log, logs have at most four topics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopicIndex {
+    /// The first topic, the event signature for non-anonymous events.
+    First,
+    Second,
+    Third,
+    Fourth,
+}
+
+impl TopicIndex {
+    fn index(self) -> usize {
+        match self {
+            TopicIndex::First => 0,
+            TopicIndex::Second => 1,
+            TopicIndex::Third => 2,
+            TopicIndex::Fourth => 3,
+        }
+    }
+}
+
 /// Filter Builder
 #[derive(Debug, Default, Clone)]
 pub struct FilterBuilder {
@@ -127,8 +215,30 @@ impl FilterBuilder {
     }
 
     /// Single address
-    pub fn address(mut self, address: Vec<H160>) -> Self {
-        self.filter.address = Some(ValueOrArray(address));
+    pub fn address(self, address: Vec<H160>) -> Self {
+        self.addresses(address)
+    }
+
+    /// Matches logs emitted by any of `addresses`, an empty list matches any
+    /// address.
+    pub fn addresses(mut self, addresses: Vec<H160>) -> Self {
+        self.filter.address = Some(ValueOrArray(addresses));
+        self
+    }
+
+    /// Matches logs whose topic at `position` is any of `values`, skipped
+    /// positions before it match any topic.
+    pub fn topic_or(mut self, position: TopicIndex, values: Vec<H256>) -> Self {
+        let position = position.index();
+        let topics = self.filter.topics.get_or_insert_with(Vec::new);
+        if topics.len() <= position {
+            topics.resize(position + 1, None);
+        }
+        topics[position] = if values.is_empty() {
+            None
+        } else {
+            Some(ValueOrArray(values))
+        };
         self
     }
 
@@ -174,6 +284,147 @@ impl FilterBuilder {
     }
 }
 
+/// The id of a filter installed on the node with `eth_newFilter`, as the hex
+/// string the node returned.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FilterId(pub String);
+
+impl fmt::Display for FilterId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A handler invoked by a [`LogRouter`] for each matching log.
+type LogHandler<T> = Box<dyn Fn(&Log) -> T>;
+
+/// Dispatches logs to handlers keyed by the log's first topic (for
+/// non-anonymous events this is the hash of the event signature).
+pub struct LogRouter<T> {
+    handlers: HashMap<H256, LogHandler<T>>,
+}
+
+impl<T> LogRouter<T> {
+    /// Creates a router with no handlers registered.
+    pub fn new() -> Self {
+        LogRouter {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Registers `handler` for logs whose first topic is `topic0`, replacing
+    /// any handler previously registered for the same topic.
+    pub fn register<F>(mut self, topic0: H256, handler: F) -> Self
+    where
+        F: Fn(&Log) -> T + 'static,
+    {
+        self.handlers.insert(topic0, Box::new(handler));
+        self
+    }
+
+    /// Routes a single log, returns `None` if the log has no topics or no
+    /// handler is registered for its first topic.
+    pub fn route(&self, log: &Log) -> Option<T> {
+        let topic0 = log.topics.first()?;
+        self.handlers.get(topic0).map(|handler| handler(log))
+    }
+
+    /// Routes a batch of logs in order, skipping logs without a handler.
+    pub fn route_all<'a, I>(&self, logs: I) -> Vec<T>
+    where
+        I: IntoIterator<Item = &'a Log>,
+    {
+        logs.into_iter().filter_map(|log| self.route(log)).collect()
+    }
+}
+
+impl<T> Default for LogRouter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for LogRouter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogRouter")
+            .field("topics", &self.handlers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Returns the topic of an event `signature`, e.g.
+/// `"Transfer(address,address,uint256)"`, as used in `topics[0]` of its logs.
+pub fn event_topic(signature: &str) -> H256 {
+    H256::from(crate::keccak256(signature.as_bytes()))
+}
+
+/// Drops logs that were already seen, e.g. when polling a filter around a
+/// reorg, keyed by block hash and log index.
+///
+/// Removed logs (`removed: true`) are passed on if the log they remove was
+/// seen, so downstream consumers can undo it, and forgotten so the log is
+/// passed on again if it is re-added.
+#[derive(Debug, Default, Clone)]
+pub struct LogDeduplicator {
+    seen: HashSet<(H256, U256)>,
+}
+
+impl LogDeduplicator {
+    /// Creates a deduplicator that has not seen any logs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `log` if it should be passed on, `None` if it is a duplicate
+    /// or removes a log that was never seen. Pending logs, without a block
+    /// hash or log index, are always passed on.
+    pub fn process(&mut self, log: Log) -> Option<Log> {
+        let key = match (log.block_hash, log.log_index) {
+            (Some(block_hash), Some(log_index)) => (block_hash, log_index),
+            _ => return Some(log),
+        };
+
+        let is_new = if log.is_removed() {
+            self.seen.remove(&key)
+        } else {
+            self.seen.insert(key)
+        };
+
+        if is_new {
+            Some(log)
+        } else {
+            None
+        }
+    }
+
+    /// Processes `logs` in order, returning the logs that are passed on.
+    pub fn process_all<I>(&mut self, logs: I) -> Vec<Log>
+    where
+        I: IntoIterator<Item = Log>,
+    {
+        logs.into_iter()
+            .filter_map(|log| self.process(log))
+            .collect()
+    }
+}
+
+/// An event that can be decoded from the logs it emits, see
+/// `TransactionReceipt::decode_logs`.
+pub trait Event: Sized {
+    /// The event signature, e.g. `"Transfer(address,address,uint256)"`.
+    const SIGNATURE: &'static str;
+
+    /// Decodes the event from `log`, returns `None` if `log` is not an
+    /// instance of this event.
+    fn from_log(log: &Log) -> Option<Self>;
+
+    /// Returns the topic of the event, see `event_topic`.
+    fn topic() -> H256 {
+        event_topic(Self::SIGNATURE)
+    }
+}
+
 /// Converts a `Topic` to an equivalent `Option<Vec<T>>`, suitable for
 /// `FilterBuilder::topics`
 fn topic_to_option<T>(topic: ethabi::Topic<T>) -> Option<Vec<T>> {
@@ -187,7 +438,7 @@ fn topic_to_option<T>(topic: ethabi::Topic<T>) -> Option<Vec<T>> {
 #[cfg(test)]
 mod tests {
     use crate::types::{
-        log::{FilterBuilder, Log},
+        log::{event_topic, FilterBuilder, Log, LogDeduplicator, LogRouter, TopicIndex},
         Address, H160, H256,
     };
     use hex_literal::hex;
@@ -207,7 +458,7 @@ mod tests {
             log_type: None,
             removed: Some(true),
         };
-        assert_eq!(true, log.is_removed());
+        assert!(log.is_removed());
     }
 
     #[test]
@@ -225,7 +476,7 @@ mod tests {
             log_type: None,
             removed: Some(false),
         };
-        assert_eq!(false, log.is_removed());
+        assert!(!log.is_removed());
     }
 
     #[test]
@@ -243,7 +494,7 @@ mod tests {
             log_type: Some("removed".into()),
             removed: None,
         };
-        assert_eq!(true, log.is_removed());
+        assert!(log.is_removed());
     }
 
     #[test]
@@ -261,7 +512,7 @@ mod tests {
             log_type: Some("mined".into()),
             removed: None,
         };
-        assert_eq!(false, log.is_removed());
+        assert!(!log.is_removed());
     }
 
     #[test]
@@ -279,7 +530,62 @@ mod tests {
             log_type: None,
             removed: None,
         };
-        assert_eq!(false, log.is_removed());
+        assert!(!log.is_removed());
+    }
+
+    #[test]
+    fn can_deserialize_log() {
+        // A USDC `Transfer` from mainnet.
+        let json = r#"{
+            "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "topics": [
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                "0x00000000000000000000000028c6c06298d514db089934071355e5743bf21d60",
+                "0x000000000000000000000000a9d1e08c7793af67e9d92fe308d5697fb81d3e43"
+            ],
+            "data": "0x0000000000000000000000000000000000000000000000000000000077359400",
+            "blockNumber": "0x1312d00",
+            "transactionHash": "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060",
+            "transactionIndex": "0x4b",
+            "blockHash": "0x3e4c1f5fe0b5e4b0b4f8e1f1d5d1c0c6c1b3e9e5d2a7f8b6c4d3e2f1a0b9c8d7",
+            "logIndex": "0xd2",
+            "removed": false
+        }"#;
+        let log: Log = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            log.address(),
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(log.topics().len(), 3);
+        assert_eq!(
+            log.topics()[0],
+            event_topic("Transfer(address,address,uint256)")
+        );
+        assert_eq!(
+            ethabi::decode(&[ethabi::ParamType::Uint(256)], &log.data().0).unwrap(),
+            vec![ethabi::Token::Uint(2_000_000_000u64.into())]
+        );
+        assert_eq!(log.block_number(), Some(20_000_000));
+        assert_eq!(
+            log.block_hash(),
+            Some(
+                "0x3e4c1f5fe0b5e4b0b4f8e1f1d5d1c0c6c1b3e9e5d2a7f8b6c4d3e2f1a0b9c8d7"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            log.transaction_hash(),
+            Some(
+                "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert!(!log.removed());
     }
 
     #[test]
@@ -303,4 +609,167 @@ mod tests {
             .build();
         assert_eq!(filter0, filter1);
     }
+
+    #[test]
+    fn serializes_single_and_multiple_addresses() {
+        let a = H160::from_low_u64_be(1);
+        let b = H160::from_low_u64_be(2);
+
+        let single = FilterBuilder::default().addresses(vec![a]).build();
+        assert_eq!(
+            serde_json::to_value(&single).unwrap(),
+            serde_json::json!({ "address": "0x0000000000000000000000000000000000000001" })
+        );
+
+        let multi = FilterBuilder::default().addresses(vec![a, b]).build();
+        assert_eq!(
+            serde_json::to_value(&multi).unwrap(),
+            serde_json::json!({
+                "address": [
+                    "0x0000000000000000000000000000000000000001",
+                    "0x0000000000000000000000000000000000000002",
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn serializes_ored_topics_with_null_padding() {
+        let transfer = H256::from_low_u64_be(1);
+        let (alice, bob) = (H256::from_low_u64_be(2), H256::from_low_u64_be(3));
+
+        let filter = FilterBuilder::default()
+            .topic_or(TopicIndex::First, vec![transfer])
+            .topic_or(TopicIndex::Third, vec![alice, bob])
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(&filter).unwrap(),
+            serde_json::json!({
+                "topics": [
+                    "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    null,
+                    [
+                        "0x0000000000000000000000000000000000000000000000000000000000000002",
+                        "0x0000000000000000000000000000000000000000000000000000000000000003",
+                    ],
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn topic_or_matches_topics() {
+        let topics =
+            |values: &[u64]| Some(values.iter().copied().map(H256::from_low_u64_be).collect());
+
+        let filter0 = FilterBuilder::default()
+            .topic_or(
+                TopicIndex::Second,
+                vec![5, 8].into_iter().map(H256::from_low_u64_be).collect(),
+            )
+            .topic_or(TopicIndex::First, vec![H256::from_low_u64_be(3)])
+            .build();
+        let filter1 = FilterBuilder::default()
+            .topics(topics(&[3]), topics(&[5, 8]), None, None)
+            .build();
+        assert_eq!(filter0, filter1);
+    }
+
+    fn log_with_topics(topics: Vec<H256>) -> Log {
+        Log {
+            address: Address::from_low_u64_be(1),
+            topics,
+            data: hex!("").into(),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        }
+    }
+
+    fn mined_log(block: u64, index: u64, removed: bool) -> Log {
+        Log {
+            block_hash: Some(H256::from_low_u64_be(block)),
+            block_number: Some(block.into()),
+            log_index: Some(index.into()),
+            removed: Some(removed),
+            ..log_with_topics(vec![])
+        }
+    }
+
+    #[test]
+    fn deduplicator_honours_removals() {
+        let mut dedup = LogDeduplicator::new();
+
+        let output = dedup.process_all(vec![
+            mined_log(1, 0, false),
+            mined_log(1, 0, false), // Duplicate.
+            mined_log(1, 1, false),
+            mined_log(1, 0, true),  // Reorged out.
+            mined_log(1, 0, true),  // Duplicate removal.
+            mined_log(2, 5, true),  // Never seen.
+            mined_log(1, 0, false), // Re-added.
+        ]);
+
+        assert_eq!(output, vec![
+            mined_log(1, 0, false),
+            mined_log(1, 1, false),
+            mined_log(1, 0, true),
+            mined_log(1, 0, false),
+        ]);
+    }
+
+    #[test]
+    fn deduplicator_passes_on_pending_logs() {
+        let mut dedup = LogDeduplicator::new();
+        let pending = log_with_topics(vec![]);
+
+        assert_eq!(dedup.process(pending.clone()), Some(pending.clone()));
+        assert_eq!(dedup.process(pending.clone()), Some(pending));
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Transfer(H256),
+        Approval(H256),
+    }
+
+    #[test]
+    fn log_router_routes_by_topic0() {
+        let transfer = H256::from_low_u64_be(0xaa);
+        let approval = H256::from_low_u64_be(0xbb);
+        let unknown = H256::from_low_u64_be(0xcc);
+
+        let router = LogRouter::new()
+            .register(transfer, |log: &Log| Event::Transfer(log.topics[1]))
+            .register(approval, |log: &Log| Event::Approval(log.topics[1]));
+
+        let logs = vec![
+            log_with_topics(vec![approval, H256::from_low_u64_be(1)]),
+            log_with_topics(vec![unknown, H256::from_low_u64_be(2)]),
+            log_with_topics(vec![]),
+            log_with_topics(vec![transfer, H256::from_low_u64_be(3)]),
+        ];
+
+        assert_eq!(router.route_all(&logs), vec![
+            Event::Approval(H256::from_low_u64_be(1)),
+            Event::Transfer(H256::from_low_u64_be(3)),
+        ]);
+        assert_eq!(router.route(&logs[1]), None);
+    }
+
+    #[test]
+    fn can_compute_event_topic() {
+        assert_eq!(
+            event_topic("Transfer(address,address,uint256)"),
+            H256::from(hex!(
+                "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            ))
+        );
+    }
 }
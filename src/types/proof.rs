@@ -0,0 +1,70 @@
+use crate::types::{Bytes, H160, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Account and storage proofs returned by `eth_getProof` (EIP-1186).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AccountProof {
+    /// Address of the account
+    pub address: H160,
+    /// Balance of the account
+    pub balance: U256,
+    /// Nonce of the account
+    pub nonce: U64,
+    /// Hash of the account code
+    #[serde(rename = "codeHash")]
+    pub code_hash: H256,
+    /// Root hash of the account storage trie
+    #[serde(rename = "storageHash")]
+    pub storage_hash: H256,
+    /// RLP encoded nodes of the path from the state root to the account
+    #[serde(rename = "accountProof")]
+    pub account_proof: Vec<Bytes>,
+    /// Proofs for each of the requested storage keys
+    #[serde(rename = "storageProof")]
+    pub storage_proof: Vec<StorageProof>,
+}
+
+/// Proof of a single storage slot.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StorageProof {
+    /// Storage key
+    pub key: U256,
+    /// Value stored at the key
+    pub value: U256,
+    /// RLP encoded nodes of the path from the storage root to the value
+    pub proof: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_deserialize_geth_proof() {
+        // Shortened response from geth, proof nodes truncated.
+        let json = r#"{
+            "address": "0x7f0d15c7faae65896648c8273b6d7e43f58fa842",
+            "accountProof": [
+                "0xf90211a0",
+                "0xf90211a1"
+            ],
+            "balance": "0x0",
+            "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            "nonce": "0x0",
+            "storageHash": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "storageProof": [
+                {
+                    "key": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                    "proof": ["0xe2a0"],
+                    "value": "0x0"
+                }
+            ]
+        }"#;
+
+        let proof: AccountProof = serde_json::from_str(json).unwrap();
+
+        assert_eq!(proof.account_proof.len(), 2);
+        assert_eq!(proof.storage_proof.len(), 1);
+        assert_eq!(proof.storage_proof[0].proof, vec![Bytes(vec![0xe2, 0xa0])]);
+    }
+}
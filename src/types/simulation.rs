@@ -0,0 +1,10 @@
+use crate::types::Bytes;
+
+/// Outcome of simulating a transaction that would succeed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationResult {
+    /// Data returned by the call.
+    pub output: Bytes,
+    /// Estimated gas used by the transaction.
+    pub gas: u64,
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Bytes, H160, H256, U256, U64};
+
+/// Account state to override for the duration of an `eth_call`, keyed by
+/// account address.
+pub type StateOverride = BTreeMap<H160, AccountOverride>;
+
+/// Overridden fields of a single account, unset fields keep their value.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AccountOverride {
+    /// Balance of the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// Nonce of the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    /// Code of the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Replaces the whole account storage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<BTreeMap<H256, H256>>,
+    /// Replaces the given storage slots, leaving the rest unchanged
+    #[serde(rename = "stateDiff")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_overridden_fields_are_serialized() {
+        let address: H160 = "0x0eb44ea45b049fc225cfdf07883dd89c7febd8f0"
+            .parse()
+            .unwrap();
+        let slot = H256::from_low_u64_be(1);
+
+        let mut overrides = StateOverride::new();
+        overrides.insert(address, AccountOverride {
+            balance: Some(1_000.into()),
+            state_diff: Some(vec![(slot, slot)].into_iter().collect()),
+            ..Default::default()
+        });
+
+        let json = serde_json::to_value(&overrides).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "0x0eb44ea45b049fc225cfdf07883dd89c7febd8f0": {
+                    "balance": "0x3e8",
+                    "stateDiff": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                            "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    },
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::types::{Bytes, H160, U256};
+use serde::{Deserialize, Serialize};
+
+/// A call frame produced by geth's `callTracer`, internal calls are nested
+/// in `calls`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CallTrace {
+    /// Call type, e.g. `CALL`, `DELEGATECALL` or `CREATE`
+    #[serde(rename = "type")]
+    pub call_type: String,
+    /// Caller
+    pub from: H160,
+    /// Callee, `None` if a contract creation failed
+    #[serde(default)]
+    pub to: Option<H160>,
+    /// Transferred value, not present for `DELEGATECALL` and `STATICCALL`
+    #[serde(default)]
+    pub value: Option<U256>,
+    /// Gas provided
+    pub gas: U256,
+    /// Gas used
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    /// Call data
+    pub input: Bytes,
+    /// Return data
+    #[serde(default)]
+    pub output: Option<Bytes>,
+    /// Error message if the call failed
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Internal calls made by this call
+    #[serde(default)]
+    pub calls: Vec<CallTrace>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_deserialize_nested_call_trace() {
+        // Captured from geth, a token transfer made via a proxy contract.
+        let json = r#"{
+            "type": "CALL",
+            "from": "0x0eb44ea45b049fc225cfdf07883dd89c7febd8f0",
+            "to": "0x1111111111111111111111111111111111111111",
+            "value": "0x0",
+            "gas": "0x1e8480",
+            "gasUsed": "0x9c0f",
+            "input": "0xa9059cbb",
+            "output": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "calls": [
+                {
+                    "type": "DELEGATECALL",
+                    "from": "0x1111111111111111111111111111111111111111",
+                    "to": "0x2222222222222222222222222222222222222222",
+                    "gas": "0x1de4b2",
+                    "gasUsed": "0x7a1b",
+                    "input": "0xa9059cbb",
+                    "output": "0x",
+                    "error": "execution reverted"
+                }
+            ]
+        }"#;
+
+        let trace: CallTrace = serde_json::from_str(json).unwrap();
+
+        assert_eq!(trace.call_type, "CALL");
+        assert_eq!(trace.gas_used, 0x9c0f.into());
+        assert_eq!(trace.calls.len(), 1);
+
+        let inner = &trace.calls[0];
+        assert_eq!(inner.call_type, "DELEGATECALL");
+        assert_eq!(inner.value, None);
+        assert_eq!(inner.error.as_deref(), Some("execution reverted"));
+        assert!(inner.calls.is_empty());
+    }
+}
@@ -1,6 +1,29 @@
-use crate::types::{Bytes, Index, Log, H160, H2048, H256, U256, U64};
+use std::convert::TryFrom;
+
+use thiserror::Error;
+
+use crate::transaction::{ChainId, Eip1559Transaction, Eip2930Transaction, LegacyTransaction};
+use crate::types::{AccessList, Address, Bytes, Event, Index, Log, H160, H2048, H256, U256, U64};
+use crate::{keccak256, recover_address, RecoverError};
 use serde::{Deserialize, Serialize};
 
+/// Error returned by `Transaction::recover_sender`.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum RecoverSenderError {
+    #[error("transaction is not signed")]
+    NotSigned,
+    #[error("transaction has no {0}")]
+    MissingField(&'static str),
+    #[error("v of {v} is not for chain id {chain_id}")]
+    WrongChain { v: u64, chain_id: ChainId },
+    #[error("unsupported transaction type: {0}")]
+    UnsupportedType(u8),
+    #[error("invalid recovery id: {0}")]
+    RecoveryId(u64),
+    #[error(transparent)]
+    Recover(#[from] RecoverError),
+}
+
 /// Description of a Transaction, pending or in the chain.
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Transaction {
@@ -24,8 +47,10 @@ pub struct Transaction {
     pub to: Option<H160>,
     /// Transfered value
     pub value: U256,
-    /// Gas Price
-    #[serde(rename = "gasPrice")]
+    /// Gas Price. For EIP-1559 and later transactions this is the effective
+    /// gas price once mined, some nodes omit it (zero) for these types, see
+    /// `effective_gas_price`.
+    #[serde(rename = "gasPrice", default)]
     pub gas_price: U256,
     /// Gas amount
     pub gas: U256,
@@ -43,6 +68,161 @@ pub struct Transaction {
     /// Raw transaction data
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub raw: Option<Bytes>,
+    /// EIP-2718 transaction type, None for legacy transactions
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<U64>,
+    /// Chain id, set for typed transactions
+    #[serde(rename = "chainId", default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U64>,
+    /// Maximum total fee per gas (EIP-1559)
+    #[serde(
+        rename = "maxFeePerGas",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_fee_per_gas: Option<U256>,
+    /// Maximum priority fee per gas (EIP-1559)
+    #[serde(
+        rename = "maxPriorityFeePerGas",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// Access list (EIP-2930)
+    #[serde(
+        rename = "accessList",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub access_list: Option<AccessList>,
+    /// Maximum fee per blob gas (EIP-4844)
+    #[serde(
+        rename = "maxFeePerBlobGas",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_fee_per_blob_gas: Option<U256>,
+    /// Versioned hashes of the blobs carried by the transaction (EIP-4844)
+    #[serde(
+        rename = "blobVersionedHashes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub blob_versioned_hashes: Option<Vec<H256>>,
+}
+
+impl Transaction {
+    /// Returns the EIP-2718 transaction type, 0 for legacy transactions.
+    ///
+    /// Types are at most 0x7f, anything larger is returned as `u8::MAX`.
+    pub fn transaction_type(&self) -> u8 {
+        self.transaction_type
+            .map_or(0, |t| u8::try_from(t.as_u64()).unwrap_or(u8::MAX))
+    }
+
+    /// Returns the price per gas the transaction pays in a block with base
+    /// fee `base_fee`.
+    ///
+    /// Legacy and EIP-2930 transactions pay their `gas_price`, EIP-1559 and
+    /// EIP-4844 transactions pay the base fee plus their priority fee, capped
+    /// at their max fee.
+    pub fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        match (
+            self.transaction_type(),
+            self.max_fee_per_gas,
+            self.max_priority_fee_per_gas,
+        ) {
+            (0 | 1, ..) => self.gas_price,
+            (_, Some(max_fee), Some(priority_fee)) => {
+                max_fee.min(base_fee.saturating_add(priority_fee))
+            }
+            _ => self.gas_price,
+        }
+    }
+
+    /// Recovers the address that signed the transaction from its signature,
+    /// e.g. to check the `from` reported by the node. Supports legacy,
+    /// EIP-2930 and EIP-1559 transactions.
+    ///
+    /// `chain_id` is used for legacy transactions, typed transactions sign
+    /// over their own `chain_id`.
+    pub fn recover_sender(&self, chain_id: ChainId) -> Result<Address, RecoverSenderError> {
+        let (v, r, s) = match (self.v, self.r, self.s) {
+            (Some(v), Some(r), Some(s)) => (v.as_u64(), r, s),
+            _ => return Err(RecoverSenderError::NotSigned),
+        };
+        let tx_chain_id = || {
+            self.chain_id
+                .map(|id| ChainId::new(id.as_u64()))
+                .ok_or(RecoverSenderError::MissingField("chainId"))
+        };
+
+        let (hash, recovery_id) = match self.transaction_type() {
+            0 => {
+                let tx = LegacyTransaction {
+                    nonce: self.nonce,
+                    gas_price: self.gas_price,
+                    gas_limit: self.gas,
+                    to: self.to,
+                    value: self.value,
+                    data: self.input.clone(),
+                };
+                match v {
+                    27 | 28 => (tx.unprotected_signing_hash(), v - 27),
+                    _ => {
+                        let recovery_id = chain_id
+                            .as_u64()
+                            .checked_mul(2)
+                            .and_then(|id| id.checked_add(35))
+                            .and_then(|offset| v.checked_sub(offset))
+                            .filter(|id| *id <= 1)
+                            .ok_or(RecoverSenderError::WrongChain { v, chain_id })?;
+                        (tx.signing_hash(chain_id), recovery_id)
+                    }
+                }
+            }
+            1 => {
+                let tx = Eip2930Transaction {
+                    chain_id: tx_chain_id()?,
+                    nonce: self.nonce,
+                    gas_price: self.gas_price,
+                    gas_limit: self.gas,
+                    to: self.to,
+                    value: self.value,
+                    data: self.input.clone(),
+                    access_list: self.access_list.clone().unwrap_or_default(),
+                };
+                (tx.signing_hash(), v)
+            }
+            2 => {
+                let tx = Eip1559Transaction {
+                    chain_id: tx_chain_id()?,
+                    nonce: self.nonce,
+                    max_priority_fee_per_gas: self
+                        .max_priority_fee_per_gas
+                        .ok_or(RecoverSenderError::MissingField("maxPriorityFeePerGas"))?,
+                    max_fee_per_gas: self
+                        .max_fee_per_gas
+                        .ok_or(RecoverSenderError::MissingField("maxFeePerGas"))?,
+                    gas_limit: self.gas,
+                    to: self.to,
+                    value: self.value,
+                    data: self.input.clone(),
+                    access_list: self.access_list.clone().unwrap_or_default(),
+                };
+                (tx.signing_hash(), v)
+            }
+            t => return Err(RecoverSenderError::UnsupportedType(t)),
+        };
+
+        let mut signature = [0u8; 65];
+        r.to_big_endian(&mut signature[..32]);
+        s.to_big_endian(&mut signature[32..64]);
+        signature[64] =
+            u8::try_from(recovery_id).map_err(|_| RecoverSenderError::RecoveryId(recovery_id))?;
+
+        Ok(recover_address(hash.as_fixed_bytes(), &signature)?)
+    }
 }
 
 /// "Receipt" of an executed transaction: details of its execution.
@@ -82,6 +262,14 @@ pub struct Receipt {
     pub logs_bloom: H2048,
 }
 
+impl Receipt {
+    /// Decodes the logs of the transaction that are instances of the event
+    /// `E`, in the order they were emitted. Other logs are skipped.
+    pub fn decode_logs<E: Event>(&self) -> Vec<E> {
+        self.logs.iter().filter_map(E::from_log).collect()
+    }
+}
+
 /// Raw bytes of a signed, but not yet sent transaction
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RawTransaction {
@@ -91,9 +279,279 @@ pub struct RawTransaction {
     pub tx: Transaction,
 }
 
+impl RawTransaction {
+    /// Returns the transaction hash, the keccak256 hash of the raw signed
+    /// transaction. This is the hash the node returns from
+    /// `eth_sendRawTransaction`.
+    pub fn hash(&self) -> H256 {
+        H256::from(keccak256(&self.raw.0))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{RawTransaction, Receipt};
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::types::AccessListItem;
+    use crate::{address_from_secret_key, rlp, secret_key_from_hex};
+
+    /// The signed transaction from EIP-155, as returned by
+    /// `eth_getTransactionByHash`.
+    fn eip155_transaction() -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "hash": "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788",
+            "nonce": "0x9",
+            "blockHash": null,
+            "blockNumber": null,
+            "transactionIndex": null,
+            "from": "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f",
+            "to": "0x3535353535353535353535353535353535353535",
+            "value": "0xde0b6b3a7640000",
+            "gasPrice": "0x4a817c800",
+            "gas": "0x5208",
+            "input": "0x",
+            "v": "0x25",
+            "r": "0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276",
+            "s": "0x67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+        }))
+        .unwrap()
+    }
+
+    /// A synthetic EIP-4844 blob transaction, in the shape geth returns for a
+    /// mined one. The hashes and signature are made up.
+    fn blob_transaction() -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "blockHash": "0x2e0ff8ff1b3ce1bb5ec3bf6ab3d08a2e08cb5a8ea5d0a1b8bcbf3ed4d0f0b5c3",
+            "blockNumber": "0x12a05f2",
+            "from": "0x0c8dd1ee3ba2b2aa0dbd6eeac1a5e4e40df2e5e5",
+            "gas": "0x5208",
+            "gasPrice": "0x1bb5e3e1e",
+            "maxFeePerGas": "0x2540be400",
+            "maxPriorityFeePerGas": "0x3b9aca00",
+            "maxFeePerBlobGas": "0x3b9aca00",
+            "hash": "0x1e9a3c5bb2f3c0a4e8d2c1b0a99887766554433221100ffeeddccbbaa9988776",
+            "input": "0x",
+            "nonce": "0x1",
+            "to": "0xff00000000000000000000000000000000000000",
+            "transactionIndex": "0x0",
+            "value": "0x0",
+            "type": "0x3",
+            "accessList": [],
+            "chainId": "0x1",
+            "blobVersionedHashes": [
+                "0x01b0a4cdd5f55589f5c5b4d46c76704bb6ce95c0a8c09f77f197a57808dded28",
+                "0x016aee5a8c47a3f7b5b4b23b01fd0fe2b4cd7a4f1b1d7f6dca4bd5b39ac0f9c1"
+            ],
+            "v": "0x1",
+            "r": "0x6e8c0d0b7c1ed2e1a6f8a9b0c1d2e3f405162738495a6b7c8d9e0f1a2b3c4d5e",
+            "s": "0x1f2e3d4c5b6a79880716253443526170809a0b1c2d3e4f5a6b7c8d9e0f1a2b3c",
+            "yParity": "0x1"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_deserialize_blob_transaction() {
+        let tx = blob_transaction();
+
+        assert_eq!(tx.transaction_type(), 3);
+        assert_eq!(tx.max_fee_per_blob_gas, Some(U256::exp10(9)));
+        let hashes = tx.blob_versioned_hashes.unwrap();
+        assert_eq!(hashes.len(), 2);
+        // Version byte of KZG commitments.
+        assert!(hashes.iter().all(|hash| hash[0] == 0x01));
+    }
+
+    #[test]
+    fn can_deserialize_each_transaction_type() {
+        let gwei = |n: u64| U256::from(n) * U256::exp10(9);
+        let base_fee = gwei(20);
+
+        let legacy = eip155_transaction();
+        assert_eq!(legacy.transaction_type(), 0);
+        assert_eq!(legacy.max_fee_per_gas, None);
+        assert_eq!(legacy.effective_gas_price(base_fee), gwei(20));
+
+        let eip2930: Transaction = serde_json::from_value(serde_json::json!({
+            "hash": "0x8b9f4b0f1c4e1b5e4a6d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a098f7e",
+            "nonce": "0x2",
+            "blockHash": null,
+            "blockNumber": null,
+            "transactionIndex": null,
+            "to": "0x3535353535353535353535353535353535353535",
+            "value": "0x0",
+            "gasPrice": "0x6fc23ac00",
+            "gas": "0x7530",
+            "input": "0x",
+            "type": "0x1",
+            "chainId": "0x1",
+            "accessList": [{
+                "address": "0x3535353535353535353535353535353535353535",
+                "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000001"]
+            }],
+        }))
+        .unwrap();
+        assert_eq!(eip2930.transaction_type(), 1);
+        assert_eq!(eip2930.access_list.as_ref().map(|list| list.len()), Some(1));
+        assert_eq!(eip2930.max_fee_per_gas, None);
+        assert_eq!(eip2930.effective_gas_price(base_fee), gwei(30));
+
+        // A pending transaction from a node that omits `gasPrice`.
+        let eip1559: Transaction = serde_json::from_value(serde_json::json!({
+            "hash": "0x7d6c5b4a39281706f5e4d3c2b1a098f7e8b9f4b0f1c4e1b5e4a6d3c2b1a09f8e",
+            "nonce": "0x3",
+            "blockHash": null,
+            "blockNumber": null,
+            "transactionIndex": null,
+            "to": "0x3535353535353535353535353535353535353535",
+            "value": "0x0",
+            "gas": "0x5208",
+            "input": "0x",
+            "type": "0x2",
+            "chainId": "0x1",
+            "maxFeePerGas": "0x9502f9000",
+            "maxPriorityFeePerGas": "0x77359400",
+            "accessList": [],
+        }))
+        .unwrap();
+        assert_eq!(eip1559.transaction_type(), 2);
+        assert_eq!(eip1559.gas_price, U256::zero());
+        assert_eq!(eip1559.max_fee_per_gas, Some(gwei(40)));
+        assert_eq!(eip1559.max_priority_fee_per_gas, Some(gwei(2)));
+        assert_eq!(eip1559.effective_gas_price(base_fee), gwei(22));
+        // Capped at the max fee.
+        assert_eq!(eip1559.effective_gas_price(gwei(39)), gwei(40));
+    }
+
+    #[test]
+    fn blob_transaction_pays_base_fee_plus_tip() {
+        let tx = blob_transaction();
+
+        assert_eq!(
+            tx.effective_gas_price(U256::from(5) * U256::exp10(9)),
+            U256::from(6) * U256::exp10(9)
+        );
+    }
+
+    #[test]
+    fn can_recover_legacy_sender() {
+        let tx = eip155_transaction();
+
+        let sender = tx.recover_sender(ChainId::MAINNET).unwrap();
+        assert_eq!(
+            sender,
+            Address::from_str("0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F").unwrap()
+        );
+        assert_eq!(sender.as_bytes(), tx.from.unwrap().as_bytes());
+    }
+
+    #[test]
+    fn legacy_sender_requires_matching_chain_id() {
+        assert_eq!(
+            eip155_transaction().recover_sender(ChainId::ROPSTEN),
+            Err(RecoverSenderError::WrongChain {
+                v: 37,
+                chain_id: ChainId::ROPSTEN
+            })
+        );
+    }
+
+    #[test]
+    fn huge_chain_id_does_not_overflow() {
+        assert!(eip155_transaction()
+            .recover_sender(ChainId::new(u64::MAX))
+            .is_err());
+    }
+
+    #[test]
+    fn unsigned_transaction_has_no_sender() {
+        let tx = Transaction {
+            v: None,
+            ..eip155_transaction()
+        };
+        assert_eq!(
+            tx.recover_sender(ChainId::MAINNET),
+            Err(RecoverSenderError::NotSigned)
+        );
+    }
+
+    /// Sets the `v`, `r` and `s` of `tx` from the last three fields of a
+    /// signed typed transaction.
+    fn with_signature(tx: Transaction, raw: &Bytes) -> Transaction {
+        let decoded = rlp::decode(&raw.0[1..]).unwrap();
+        let fields = decoded.as_list().unwrap();
+        let field =
+            |i: usize| U256::from_big_endian(fields[fields.len() - 3 + i].as_bytes().unwrap());
+
+        Transaction {
+            v: Some(field(0).as_u64().into()),
+            r: Some(field(1)),
+            s: Some(field(2)),
+            ..tx
+        }
+    }
+
+    #[test]
+    fn can_recover_typed_senders() {
+        let sk = secret_key_from_hex(&"46".repeat(32)).unwrap();
+        let want = address_from_secret_key(&sk);
+        let access_list = vec![AccessListItem {
+            address: H160::repeat_byte(0x11),
+            storage_keys: vec![H256::zero()],
+        }];
+        let base = Transaction {
+            nonce: 1.into(),
+            to: Some(H160::repeat_byte(0x35)),
+            value: U256::exp10(18),
+            gas: 30_000.into(),
+            chain_id: Some(5.into()),
+            access_list: Some(access_list.clone()),
+            ..Default::default()
+        };
+
+        let eip2930 = Eip2930Transaction {
+            chain_id: ChainId::GOERLI,
+            nonce: 1.into(),
+            gas_price: U256::exp10(9),
+            gas_limit: 30_000.into(),
+            to: base.to,
+            value: base.value,
+            data: Bytes::default(),
+            access_list: access_list.clone(),
+        };
+        let tx = with_signature(
+            Transaction {
+                transaction_type: Some(1.into()),
+                gas_price: eip2930.gas_price,
+                ..base.clone()
+            },
+            &eip2930.sign(&sk),
+        );
+        assert_eq!(tx.recover_sender(ChainId::MAINNET).unwrap(), want);
+
+        let eip1559 = Eip1559Transaction {
+            chain_id: ChainId::GOERLI,
+            nonce: 1.into(),
+            max_priority_fee_per_gas: U256::exp10(9),
+            max_fee_per_gas: U256::from(30) * U256::exp10(9),
+            gas_limit: 30_000.into(),
+            to: base.to,
+            value: base.value,
+            data: Bytes::default(),
+            access_list,
+        };
+        let tx = with_signature(
+            Transaction {
+                transaction_type: Some(2.into()),
+                max_priority_fee_per_gas: Some(eip1559.max_priority_fee_per_gas),
+                max_fee_per_gas: Some(eip1559.max_fee_per_gas),
+                ..base
+            },
+            &eip1559.sign(&sk),
+        );
+        assert_eq!(tx.recover_sender(ChainId::MAINNET).unwrap(), want);
+    }
 
     #[test]
     fn test_deserialize_receipt() {
@@ -184,4 +642,96 @@ mod tests {
 
         let _tx: RawTransaction = serde_json::from_str(tx_str).unwrap();
     }
+
+    #[test]
+    fn raw_transaction_hash_matches_geth() {
+        let raw = "0xf85d01018094f3b3138e5eb1c75b43994d1bb760e2f9f735789680801ca06484d00575e961a7db35ebe5badaaca5cb7ee65d1f2f22f22da87c238b99d30da07a85d65797e4b555c1d3f64beebb2cb6f16a6fbd40c43cc48451eaf85305f66e";
+        let tx = RawTransaction {
+            raw: serde_json::from_value(serde_json::json!(raw)).unwrap(),
+            tx: Default::default(),
+        };
+
+        // Hash returned by geth for the raw transaction above.
+        let want: H256 = "0x0a32fb4e18bc6f7266a164579237b1b5c74271d453c04eab70444ca367d38418"
+            .parse()
+            .unwrap();
+        assert_eq!(tx.hash(), want);
+    }
+
+    #[test]
+    fn can_decode_transfer_events_from_receipt() {
+        // A synthetic receipt of a USDC `transfer` (the hashes and block are
+        // made up) with an unrelated `Approval` log added in between.
+        let receipt = r#"{
+            "transactionHash": "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060",
+            "transactionIndex": "0x4b",
+            "blockHash": "0x3e4c1f5fe0b5e4b0b4f8e1f1d5d1c0c6c1b3e9e5d2a7f8b6c4d3e2f1a0b9c8d7",
+            "blockNumber": "0x1312d00",
+            "cumulativeGasUsed": "0x8a1c3b",
+            "gasUsed": "0xa9d9",
+            "contractAddress": null,
+            "status": "0x1",
+            "root": null,
+            "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            "logs": [
+                {
+                    "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "topics": [
+                        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                        "0x00000000000000000000000028c6c06298d514db089934071355e5743bf21d60",
+                        "0x000000000000000000000000a9d1e08c7793af67e9d92fe308d5697fb81d3e43"
+                    ],
+                    "data": "0x0000000000000000000000000000000000000000000000000000000077359400",
+                    "blockNumber": "0x1312d00",
+                    "logIndex": "0xd2",
+                    "removed": false
+                },
+                {
+                    "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "topics": [
+                        "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925",
+                        "0x00000000000000000000000028c6c06298d514db089934071355e5743bf21d60",
+                        "0x000000000000000000000000a9d1e08c7793af67e9d92fe308d5697fb81d3e43"
+                    ],
+                    "data": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "blockNumber": "0x1312d00",
+                    "logIndex": "0xd3",
+                    "removed": false
+                },
+                {
+                    "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "topics": [
+                        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                        "0x000000000000000000000000a9d1e08c7793af67e9d92fe308d5697fb81d3e43",
+                        "0x0000000000000000000000000000000000000000000000000000000000000001"
+                    ],
+                    "data": "0x00000000000000000000000000000000000000000000000000000000000f4240",
+                    "blockNumber": "0x1312d00",
+                    "logIndex": "0xd4",
+                    "removed": false
+                }
+            ]
+        }"#;
+        let receipt: Receipt = serde_json::from_str(receipt).unwrap();
+
+        let transfers = receipt.decode_logs::<crate::types::Erc20Transfer>();
+
+        let usdc: H160 = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+            .parse()
+            .unwrap();
+        let exchange: H160 = "0x28c6c06298d514db089934071355e5743bf21d60"
+            .parse()
+            .unwrap();
+        let user: H160 = "0xa9d1e08c7793af67e9d92fe308d5697fb81d3e43"
+            .parse()
+            .unwrap();
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].token, usdc);
+        assert_eq!(transfers[0].from, exchange);
+        assert_eq!(transfers[0].to, user);
+        assert_eq!(transfers[0].value, U256::from(2_000_000_000u64));
+        assert_eq!(transfers[1].from, user);
+        assert_eq!(transfers[1].to, H160::from_low_u64_be(1));
+        assert_eq!(transfers[1].value, U256::from(1_000_000u64));
+    }
 }
@@ -32,6 +32,18 @@ pub struct CallRequest {
     pub data: Option<Bytes>,
 }
 
+/// The parameters needed to sign a legacy transaction, see
+/// `Client::prepare_transaction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct TxParams {
+    /// Next nonce of the sender, counting pending transactions.
+    pub nonce: u64,
+    /// Current gas price, in wei.
+    pub gas_price: U256,
+    /// Estimated gas used by the transaction.
+    pub gas_limit: U256,
+}
+
 /// Send Transaction Parameters
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct TransactionRequest {
@@ -105,6 +117,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_serialize_call_request_quantities_as_hex() {
+        let call_request = CallRequest {
+            from: Some(H160::from_low_u64_be(1)),
+            to: Some(H160::from_low_u64_be(5)),
+            gas: Some(0.into()),
+            gas_price: Some(20_000_000_000u64.into()),
+            value: Some(0.into()),
+            data: Some(vec![].into()),
+        };
+
+        let serialized = serde_json::to_value(&call_request).unwrap();
+
+        // Quantities are hex with no leading zeros (zero is "0x0"), data is
+        // hex bytes (empty is "0x").
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "from": "0x0000000000000000000000000000000000000001",
+                "to": "0x0000000000000000000000000000000000000005",
+                "gas": "0x0",
+                "gasPrice": "0x4a817c800",
+                "value": "0x0",
+                "data": "0x"
+            })
+        );
+    }
+
     #[test]
     fn should_deserialize_call_request() {
         let serialized = r#"{
@@ -113,7 +153,7 @@ mod tests {
   "value": "0x4c4b40",
   "data": "0x010203"
 }"#;
-        let deserialized: CallRequest = serde_json::from_str(&serialized).unwrap();
+        let deserialized: CallRequest = serde_json::from_str(serialized).unwrap();
 
         assert_eq!(deserialized.from, None);
         assert_eq!(deserialized.to, Some(H160::from_low_u64_be(5)));
@@ -166,7 +206,7 @@ mod tests {
     "block": 5
   }
 }"#;
-        let deserialized: TransactionRequest = serde_json::from_str(&serialized).unwrap();
+        let deserialized: TransactionRequest = serde_json::from_str(serialized).unwrap();
 
         assert_eq!(deserialized.from, H160::from_low_u64_be(5));
         assert_eq!(deserialized.to, None);
@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use crate::types::{Transaction, H160, U64};
+use serde::{Deserialize, Serialize};
+
+/// Number of transactions in the transaction pool (`txpool_status`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct TxpoolStatus {
+    /// Number of pending transactions
+    pub pending: U64,
+    /// Number of queued transactions
+    pub queued: U64,
+}
+
+/// Transactions in the transaction pool (`txpool_content`), grouped by
+/// sender and keyed by nonce.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TxpoolContent {
+    /// Pending transactions
+    pub pending: BTreeMap<H160, BTreeMap<String, Transaction>>,
+    /// Queued transactions
+    pub queued: BTreeMap<H160, BTreeMap<String, Transaction>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_deserialize_txpool_status() {
+        let status: TxpoolStatus =
+            serde_json::from_str(r#"{"pending": "0xa", "queued": "0x7"}"#).unwrap();
+
+        assert_eq!(status.pending, U64::from(10));
+        assert_eq!(status.queued, U64::from(7));
+    }
+
+    #[test]
+    fn can_deserialize_txpool_content() {
+        let json = r#"{
+            "pending": {
+                "0x0216d5032f356960cd3749c31ab34eeff21b3395": {
+                    "806": {
+                        "blockHash": null,
+                        "blockNumber": null,
+                        "from": "0x0216d5032f356960cd3749c31ab34eeff21b3395",
+                        "gas": "0x5208",
+                        "gasPrice": "0xba43b7400",
+                        "hash": "0xaf953a2d01f55cfe080c0c94150a60105e8ac3d51153058a1f03dd239dd08586",
+                        "input": "0x",
+                        "nonce": "0x326",
+                        "to": "0x7f69a91a3cf4be60020fb58b893b7cbb65376db8",
+                        "transactionIndex": null,
+                        "value": "0x19a99f0cf456000"
+                    }
+                }
+            },
+            "queued": {}
+        }"#;
+
+        let content: TxpoolContent = serde_json::from_str(json).unwrap();
+
+        let sender: H160 = "0x0216d5032f356960cd3749c31ab34eeff21b3395"
+            .parse()
+            .unwrap();
+        let tx = &content.pending[&sender]["806"];
+        assert_eq!(tx.nonce, 806.into());
+        assert!(content.queued.is_empty());
+    }
+}
@@ -1,6 +1,32 @@
 pub use ethereum_types::{
     BigEndianHash, Bloom as H2048, H128, H160, H256, H512, H520, H64, U128, U256, U64,
 };
+use thiserror::Error;
+
+/// Parses a hex encoded quantity as returned by JSON-RPC, e.g. `"0x1b4"`.
+/// The `0x` prefix is optional.
+pub fn parse_quantity(hex: &str) -> Result<U256, ParseQuantityError> {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    if digits.is_empty() {
+        return Err(ParseQuantityError::Empty);
+    }
+    if digits.len() > 64 {
+        return Err(ParseQuantityError::TooLarge);
+    }
+
+    U256::from_str_radix(digits, 16).map_err(|_| ParseQuantityError::InvalidHex(hex.to_owned()))
+}
+
+/// Errors returned when parsing a hex quantity.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ParseQuantityError {
+    #[error("quantity has no digits")]
+    Empty,
+    #[error("quantity is not valid hex: {0:?}")]
+    InvalidHex(String),
+    #[error("quantity is too large")]
+    TooLarge,
+}
 
 #[cfg(test)]
 mod tests {
@@ -8,6 +34,32 @@ mod tests {
 
     type Res = Result<U256, serde_json::Error>;
 
+    #[test]
+    fn can_parse_quantity() {
+        assert_eq!(parse_quantity("0x1b4"), Ok(U256::from(0x1b4)));
+        assert_eq!(parse_quantity("1b4"), Ok(U256::from(0x1b4)));
+        assert_eq!(parse_quantity("0x0"), Ok(U256::zero()));
+        assert_eq!(parse_quantity("0x3B9ACA00"), Ok(U256::from(1_000_000_000)));
+        assert_eq!(
+            parse_quantity(&format!("0x{}", "f".repeat(64))),
+            Ok(U256::MAX)
+        );
+    }
+
+    #[test]
+    fn parse_quantity_rejects_invalid_input() {
+        assert_eq!(parse_quantity(""), Err(ParseQuantityError::Empty));
+        assert_eq!(parse_quantity("0x"), Err(ParseQuantityError::Empty));
+        assert_eq!(
+            parse_quantity("0xzz"),
+            Err(ParseQuantityError::InvalidHex("0xzz".to_owned()))
+        );
+        assert_eq!(
+            parse_quantity(&format!("0x1{}", "0".repeat(64))),
+            Err(ParseQuantityError::TooLarge)
+        );
+    }
+
     #[test]
     fn should_compare_correctly() {
         let mut arr = [0u8; 32];
@@ -0,0 +1,145 @@
+//! Polls for new blocks over plain HTTP, for endpoints without subscription
+//! support.
+
+use std::thread;
+use std::time::Duration;
+
+use crate::api::{Client, ClientError};
+use crate::types::{BlockHeader, BlockNumber};
+
+/// An iterator over the headers of new blocks, returned by
+/// `Client::watch_blocks`.
+///
+/// Each call to `next` blocks until there is a block that has not been
+/// returned yet, starting with the current head. If the head moves back
+/// (a reorg) the headers from the new head onwards are returned again.
+#[derive(Debug)]
+pub struct BlockWatcher<'a> {
+    client: &'a Client,
+    poll_interval: Duration,
+    next: Option<u64>,
+}
+
+impl<'a> BlockWatcher<'a> {
+    pub fn new(client: &'a Client, poll_interval: Duration) -> Self {
+        BlockWatcher {
+            client,
+            poll_interval,
+            next: None,
+        }
+    }
+
+    fn next_header(&mut self) -> Result<BlockHeader, ClientError> {
+        loop {
+            let latest = self.client.block_number()?;
+            let next = match self.next {
+                // The head moved back, re-emit from the new head.
+                Some(next) if latest.saturating_add(1) < next => {
+                    log::debug!("head regressed from {} to {}", next - 1, latest);
+                    latest
+                }
+                Some(next) => next,
+                None => latest,
+            };
+
+            if next <= latest {
+                if let Some(header) = self.client.get_block_header(BlockNumber::from(next))? {
+                    self.next = Some(next.saturating_add(1));
+                    return Ok(header);
+                }
+            }
+            self.next = Some(next);
+            thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+impl Iterator for BlockWatcher<'_> {
+    type Item = Result<BlockHeader, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_header())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::{self, MockServer};
+
+    /// A node whose head follows `heads`, one step per `eth_blockNumber`
+    /// call, then stays at the last head.
+    fn node(heads: &'static [u64]) -> MockServer {
+        let calls = AtomicUsize::new(0);
+
+        MockServer::start(move |request| {
+            let request = request.json();
+            let result = match request["method"].as_str() {
+                Some("eth_blockNumber") => {
+                    let i = calls.fetch_add(1, Ordering::SeqCst).min(heads.len() - 1);
+                    json!(format!("0x{:x}", heads[i]))
+                }
+                Some("eth_getBlockByNumber") => header_json(&request["params"][0]),
+                _ => json!(null),
+            };
+            test_util::response(result)
+        })
+    }
+
+    fn header_json(number: &serde_json::Value) -> serde_json::Value {
+        json!({
+            "number": number,
+            "hash": "0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331",
+            "parentHash": "0x9646252be9520f6e71339a8df9c55e4d7619deeb018d2a3f2d21fc165dde5eb5",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "miner": "0x0000000000000000000000000000000000000000",
+            "stateRoot": "0xd5855eb08b3387c0af375e9cdb6acfc05eb8f519e419b874b6ff2ffda7ed1dff",
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "gasUsed": "0x0",
+            "gasLimit": "0x9f759",
+            "extraData": "0x",
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "timestamp": "0x54e34e8e",
+            "difficulty": "0x27f07",
+        })
+    }
+
+    fn numbers(watcher: BlockWatcher<'_>, n: usize) -> Vec<u64> {
+        watcher
+            .take(n)
+            .map(|header| header.unwrap().number.unwrap().as_u64())
+            .collect()
+    }
+
+    #[test]
+    fn emits_each_block_once_in_order() {
+        let server = node(&[5, 5, 5, 6, 9]);
+        let client = Client::new(server.url());
+
+        let watcher = client.watch_blocks(Duration::from_millis(1));
+        assert_eq!(numbers(watcher, 5), vec![5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn re_emits_after_head_regresses() {
+        let server = node(&[5, 6, 8, 8, 6, 7]);
+        let client = Client::new(server.url());
+
+        let watcher = client.watch_blocks(Duration::from_millis(1));
+        assert_eq!(numbers(watcher, 6), vec![5, 6, 7, 8, 6, 7]);
+    }
+
+    #[test]
+    fn head_at_u64_max_does_not_overflow() {
+        let server = node(&[u64::MAX - 1, u64::MAX, 3]);
+        let client = Client::new(server.url());
+
+        let watcher = client.watch_blocks(Duration::from_millis(1));
+        assert_eq!(numbers(watcher, 3), vec![u64::MAX - 1, u64::MAX, 3]);
+    }
+}
@@ -2,11 +2,12 @@
 use std::str::FromStr;
 
 use anyhow::Result;
-use clarity::{Address, PrivateKey, Transaction, Uint256};
 use conquer_once::Lazy;
 
 use ethereum::api::{Client, Url};
-use ethereum::types::{BlockNumber, CallRequest};
+use ethereum::transaction::{ChainId, LegacyTransaction};
+use ethereum::types::{BlockNumber, CallRequest, H256, U256};
+use ethereum::{address_from_secret_key, secret_key_from_hex, Address, SecretKey};
 
 // Set up a project at infura.io (set network to Ropsten).
 static PROJECT_ID: &str = env!("INFURA_PROJECT_ID");
@@ -17,21 +18,21 @@ const ENDPOINT: &str = "https://ropsten.infura.io/v3/";
 const CHAIN_ID: u32 = 3; // Ropsten
 
 fn alice() -> Address {
-    alice_private_key().to_public_key().unwrap()
+    address_from_secret_key(&alice_private_key())
 }
 
 fn bob() -> Address {
-    bob_private_key().to_public_key().unwrap()
+    address_from_secret_key(&bob_private_key())
 }
 
-fn alice_private_key() -> PrivateKey {
+fn alice_private_key() -> SecretKey {
     let key_material = "aaaaaaaa6422720fab7fee3f875fc2cb399af859156d8f189b930e485674e472";
-    PrivateKey::from_str(&key_material).unwrap()
+    secret_key_from_hex(key_material).unwrap()
 }
 
-fn bob_private_key() -> PrivateKey {
+fn bob_private_key() -> SecretKey {
     let key_material = "bbbbbbbb6422720fab7fee3f875fc2cb399af859156d8f189b930e485674e472";
-    PrivateKey::from_str(&key_material).unwrap()
+    secret_key_from_hex(key_material).unwrap()
 }
 
 static CLIENT: Lazy<Client> = Lazy::new(|| {
@@ -76,6 +77,41 @@ fn connected_to_expected_network() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn node_is_listening() -> Result<()> {
+    let cli = client();
+    assert!(cli.is_listening()?);
+
+    Ok(())
+}
+
+#[test]
+fn can_get_peer_count() -> Result<()> {
+    let cli = client();
+    let count = cli.peer_count()?;
+    println!("Node peer count: {}", count);
+
+    Ok(())
+}
+
+#[test]
+fn can_get_mining_status() -> Result<()> {
+    let cli = client();
+    let mining = cli.is_mining()?;
+    println!("Node is mining: {}", mining);
+
+    Ok(())
+}
+
+#[test]
+fn can_get_hashrate() -> Result<()> {
+    let cli = client();
+    let hashrate = cli.hashrate()?;
+    println!("Node hashrate: {}", hashrate);
+
+    Ok(())
+}
+
 #[test]
 fn can_get_balance() -> Result<()> {
     let cli = client();
@@ -89,6 +125,17 @@ fn can_get_balance() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn can_get_proof_for_funded_account() -> Result<()> {
+    let cli = client();
+
+    let proof = cli.get_proof(alice(), &[H256::zero()], latest())?;
+    assert!(!proof.account_proof.is_empty());
+    assert_eq!(proof.storage_proof.len(), 1);
+
+    Ok(())
+}
+
 #[test]
 fn can_get_transaction_count() -> Result<()> {
     let cli = client();
@@ -102,6 +149,38 @@ fn can_get_transaction_count() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn genesis_block_has_no_transactions() -> Result<()> {
+    let cli = client();
+    let count = cli.get_block_transaction_count(BlockNumber::Earliest)?;
+    assert_eq!(count, 0);
+
+    Ok(())
+}
+
+#[test]
+fn block_iter_yields_contiguous_blocks() -> Result<()> {
+    let client = client();
+    let numbers = client
+        .block_iter::<H256>(0.into(), 3.into(), false)?
+        .map(|block| block.map(|block| block.number.map(|n| n.as_u64())))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(numbers, vec![Some(0), Some(1), Some(2), Some(3)]);
+    Ok(())
+}
+
+#[test]
+fn pending_nonce_is_at_least_latest_nonce() -> Result<()> {
+    let cli = client();
+
+    let latest = cli.get_transaction_count(alice(), latest())?;
+    let pending = cli.get_pending_nonce(alice())?;
+    assert!(pending >= latest);
+
+    Ok(())
+}
+
 #[test]
 fn can_get_gas_price() -> Result<()> {
     let cli = client();
@@ -131,24 +210,51 @@ fn can_send_transaction() -> Result<()> {
     let cli = client();
     let nonce = cli.get_transaction_count(alice(), latest())?;
 
-    let gas_limit = 21_000u32;
-    let gas_price = cli.gas_price()?;
-    let value = Uint256::from_str("10_000_000_000_000_000")?; // 0.01 ether
-
-    let tx = Transaction {
+    let tx = LegacyTransaction {
         nonce: nonce.into(),
-        gas_price,
-        gas_limit: gas_limit.into(),
-        to: bob(),
-        value,
-        data: Vec::new(),
-        signature: None, // Not signed. Yet.
+        gas_price: cli.gas_price()?,
+        gas_limit: 21_000.into(),
+        to: Some(bob()),
+        value: U256::exp10(16), // 0.01 ether
+        data: Vec::new().into(),
     };
 
-    let tx_signed: Transaction = tx.sign(&alice_private_key(), None);
-    assert!(tx_signed.is_valid());
+    let signed = tx.sign(&alice_private_key(), ChainId::new(CHAIN_ID.into()));
+
+    let _hash = cli.send_raw_transaction(signed.to_hex())?;
+
+    Ok(())
+}
+
+// Resolving names needs mainnet ENS, so only runs if `MAINNET_RPC_URL` is set.
+#[test]
+fn can_resolve_ens_name_on_mainnet() -> Result<()> {
+    let url = match std::env::var("MAINNET_RPC_URL") {
+        Ok(url) => Url::from_str(&url)?,
+        Err(_) => return Ok(()),
+    };
+    let cli = Client::new(url);
+
+    let address = cli.resolve_ens("vitalik.eth")?;
+    let want = Address::from_str("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045").unwrap();
+    assert_eq!(address, Some(want));
+
+    Ok(())
+}
+
+// Like `can_resolve_ens_name_on_mainnet`, only runs if `MAINNET_RPC_URL` is
+// set.
+#[test]
+fn can_lookup_ens_name_on_mainnet() -> Result<()> {
+    let url = match std::env::var("MAINNET_RPC_URL") {
+        Ok(url) => Url::from_str(&url)?,
+        Err(_) => return Ok(()),
+    };
+    let cli = Client::new(url);
 
-    let _hash = cli.send_raw_transaction(tx_signed.to_string())?;
+    let address = Address::from_str("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045").unwrap();
+    let name = cli.lookup_ens(address)?;
+    assert_eq!(name.as_deref(), Some("vitalik.eth"));
 
     Ok(())
 }